@@ -9,7 +9,8 @@ use solana_sdk::{
     signer::{EncodableKey, Signer},
     transaction::Transaction,
 };
-use solana_verifier::{Entrypoint, PROGRAM_ID, ProofAccount};
+use solana_verifier::heap::HEAP_FRAME;
+use solana_verifier::{AccountHeader, Entrypoint, PROGRAM_ID, ProofAccount};
 use std::{path::PathBuf, str::FromStr};
 use swiftness::{TransformTo, parse, types::StarkProof};
 
@@ -41,7 +42,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let data_address = Pubkey::from_str("4tLiFAEWRcssT763nxBnKh1eocNeptpSxEtAwH8r96W5").unwrap();
     let data = client.get_account_data(&data_address).await?;
 
-    let proof_account = bytemuck::from_bytes::<ProofAccount>(&data[1..]);
+    let proof_account = bytemuck::from_bytes::<ProofAccount>(&data[size_of::<AccountHeader>()..]);
     if proof_account.proof != read_proof() {
         eprintln!("data in the account does not match the proof");
         // } else if let Err(e) = verify_recursive_bytes(&mut data) {
@@ -51,14 +52,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let ix = Instruction {
         program_id: Pubkey::from_str(PROGRAM_ID)?,
         accounts: vec![AccountMeta::new(data_address, false)],
-        data: bincode::serialize(&Entrypoint::VerifyProof).unwrap(),
+        data: Entrypoint::VerifyProof.pack(),
     };
 
     let blockhash = client.get_latest_blockhash().await?;
 
     let tx = Transaction::new_signed_with_payer(
         &[
-            ComputeBudgetInstruction::request_heap_frame(32 * 1024),
+            ComputeBudgetInstruction::request_heap_frame(HEAP_FRAME as u32),
             // ComputeBudgetInstruction::set_compute_unit_limit(1400_000),
             ix,
         ],
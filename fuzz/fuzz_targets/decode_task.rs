@@ -0,0 +1,34 @@
+//! Decodes a task from arbitrary bytes, and views it over the account of a valid proof.
+#![no_main]
+
+use std::sync::{LazyLock, Mutex};
+
+use libfuzzer_sys::fuzz_target;
+use solana_verifier::ProofAccount;
+use solana_verifier::task::{RawTask, Tasks};
+use swiftness::{TransformTo, parse};
+
+static ACCOUNT: LazyLock<Mutex<Vec<u8>>> = LazyLock::new(|| {
+    let mut account = vec![0u8; size_of::<ProofAccount>()];
+    bytemuck::from_bytes_mut::<ProofAccount>(&mut account).proof =
+        parse(include_str!("../../resources/saya.json"))
+            .unwrap()
+            .transform_to();
+    Mutex::new(account)
+});
+
+fuzz_target!(|raw: RawTask| {
+    let Ok(task) = Tasks::try_from(&raw) else {
+        return;
+    };
+    assert_eq!(RawTask::from(task), raw);
+
+    let mut account = ACCOUNT.lock().unwrap();
+    let ProofAccount {
+        proof,
+        cache,
+        intermediate,
+        ..
+    } = bytemuck::from_bytes_mut::<ProofAccount>(&mut account);
+    let _ = task.view(proof, cache, intermediate);
+});
@@ -0,0 +1,12 @@
+//! Writes the IDL to `idl/solana_verifier.json`, run by `cargo idl` after changing the instructions
+//! or the account layout.
+
+use solana_verifier::idl;
+
+fn main() -> std::io::Result<()> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(idl::PATH);
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(&path, idl::render())?;
+    println!("IDL written to {}", path.display());
+    Ok(())
+}
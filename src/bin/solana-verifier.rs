@@ -0,0 +1,239 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::{EncodableKey, Signer};
+use solana_verifier::client::{self, Client};
+use solana_verifier::idl;
+use solana_verifier::task::TableDecommitTarget;
+use solana_verifier::{PROGRAM_ID, VerificationStage};
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Upload and verify Cairo proofs with the Solana verifier program"
+)]
+struct Cli {
+    /// JSON RPC URL of the cluster.
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair paying for the transactions, defaults to the Solana CLI keypair.
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    /// Address of the verifier program.
+    #[arg(long, global = true, default_value = PROGRAM_ID)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert a JSON proof into the account image.
+    Prepare {
+        proof: PathBuf,
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+    /// Print the IDL of the program.
+    Idl,
+    /// Create an account large enough for the account image.
+    CreateAccount {
+        image: PathBuf,
+        /// Where to store the keypair of the new account.
+        #[arg(long, default_value = "proof-account.json")]
+        account_keypair: PathBuf,
+        /// Overwrite the keypair file if it already exists.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Publish the account image, resending only the fragments missing on chain.
+    Upload {
+        image: PathBuf,
+        account: Pubkey,
+        /// File tracking the progress, defaults to the image path with an `upload` extension.
+        #[arg(long)]
+        state: Option<PathBuf>,
+    },
+    /// Schedule verification of the uploaded proof.
+    Schedule {
+        account: Pubkey,
+        /// Leave table decommitments to work accounts.
+        #[arg(long)]
+        parallel: bool,
+        /// Schedule every task upfront instead of splitting them while cranking.
+        #[arg(long, conflicts_with = "parallel")]
+        eager: bool,
+    },
+    /// Execute verification tasks until the proof is verified, or until the join of a parallel schedule.
+    Crank { account: Pubkey },
+    /// Verify a table exported by a parallel schedule in a new work account.
+    VerifyWork {
+        account: Pubkey,
+        #[arg(value_enum)]
+        table: Table,
+    },
+    /// Mark the table of a verified work account as verified in the proof account.
+    MergeWork { account: Pubkey, work: Pubkey },
+    /// Show the verification progress.
+    Status { account: Pubkey },
+    /// Show the program hash and output of the verified proof.
+    Result { account: Pubkey },
+}
+
+/// Tables which can be verified in work accounts.
+#[derive(Clone, Copy, ValueEnum)]
+enum Table {
+    Original,
+    Interaction,
+    Composition,
+}
+
+impl From<Table> for TableDecommitTarget {
+    fn from(table: Table) -> Self {
+        match table {
+            Table::Original => TableDecommitTarget::Original,
+            Table::Interaction => TableDecommitTarget::Interaction,
+            Table::Composition => TableDecommitTarget::Composition,
+        }
+    }
+}
+
+fn default_keypair() -> client::Result<PathBuf> {
+    let home = std::env::var("HOME").map_err(|_| "HOME is not set, pass --keypair")?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+#[tokio::main]
+async fn main() -> client::Result<()> {
+    let cli = Cli::parse();
+
+    if let Command::Prepare { proof, output } = &cli.command {
+        let image = client::prepare(&std::fs::read_to_string(proof)?)?;
+        std::fs::write(output, &image)?;
+        println!(
+            "Account image of {} bytes written to {}",
+            image.len(),
+            output.display()
+        );
+        return Ok(());
+    }
+
+    if let Command::Idl = &cli.command {
+        print!("{}", idl::render());
+        return Ok(());
+    }
+
+    let keypair = match cli.keypair {
+        Some(keypair) => keypair,
+        None => default_keypair()?,
+    };
+    let payer = Keypair::read_from_file(&keypair).map_err(|e| e.to_string())?;
+    let rpc = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+    let client = Client::new(rpc, payer, cli.program_id);
+
+    match cli.command {
+        Command::Prepare { .. } | Command::Idl => unreachable!("handled above"),
+        Command::CreateAccount {
+            image,
+            account_keypair,
+            force,
+        } => {
+            if account_keypair.exists() && !force {
+                return Err(format!(
+                    "{} already exists, pass --force to overwrite it",
+                    account_keypair.display()
+                )
+                .into());
+            }
+            let image = std::fs::read(image)?;
+            let account = Keypair::new();
+            account
+                .write_to_file(&account_keypair)
+                .map_err(|e| e.to_string())?;
+            client.create_account(&account, &image).await?;
+            println!("Created account {}", account.pubkey());
+        }
+        Command::Upload {
+            image,
+            account,
+            state,
+        } => {
+            let state = state.unwrap_or_else(|| image.with_extension("upload"));
+            client
+                .upload(&account, &std::fs::read(&image)?, Some(&state), |state| {
+                    println!(
+                        "confirmed {}/{} fragments",
+                        state.n_confirmed(),
+                        state.confirmed.len()
+                    );
+                })
+                .await?;
+            println!("Uploaded to {account}");
+        }
+        Command::Schedule {
+            account,
+            parallel,
+            eager,
+        } => {
+            match eager {
+                true => client.schedule_eager(&account).await?,
+                false => client.schedule(&account, parallel).await?,
+            };
+            println!("Scheduled {account}");
+        }
+        Command::Crank { account } => {
+            let stage = client
+                .crank(&account, |progress| {
+                    println!(
+                        "{:>6.2}% executed {}, remaining {}, next {:?}",
+                        progress.percentage(),
+                        progress.executed,
+                        progress.remaining,
+                        progress.current
+                    );
+                })
+                .await?;
+            match stage {
+                VerificationStage::Verified => println!("Verified {account}"),
+                _ => println!(
+                    "Waiting for the work accounts of {account}, run verify-work and merge-work"
+                ),
+            }
+        }
+        Command::VerifyWork { account, table } => {
+            let work = client.verify_work(&account, table.into()).await?;
+            println!("Verified in work account {work}");
+        }
+        Command::MergeWork { account, work } => {
+            client.merge_work(&account, &work).await?;
+            println!("Merged {work} into {account}");
+        }
+        Command::Status { account } => {
+            let progress = client.progress(&account).await?;
+            println!("stage: {:?}", progress.stage);
+            println!("executed: {}", progress.executed);
+            println!("remaining: {}", progress.remaining);
+            println!("current: {:?}", progress.current);
+            println!("progress: {:.2}%", progress.percentage());
+        }
+        Command::Result { account } => {
+            let (program_hash, output) = client.result(&account).await?;
+            println!("program hash: {program_hash}");
+            println!("output: {output:?}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::Entrypoint;
+use crate::heap::HEAP_FRAME;
+use crate::task::TableDecommitTarget;
+
+fn instruction(program_id: &Pubkey, account: &Pubkey, entrypoint: &Entrypoint) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new(*account, false)],
+        data: entrypoint.pack(),
+    }
+}
+
+/// Heap frame for the instructions running tasks, all of it used by the `custom-heap` allocator.
+pub fn request_heap_frame() -> Instruction {
+    ComputeBudgetInstruction::request_heap_frame(HEAP_FRAME as u32)
+}
+
+pub fn publish_fragment(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    offset: usize,
+    data: &[u8],
+) -> Instruction {
+    instruction(
+        program_id,
+        account,
+        &Entrypoint::PublishFragment {
+            offset: offset.try_into().expect("offset doesn't fit in u32"),
+            data,
+        },
+    )
+}
+
+pub fn schedule(program_id: &Pubkey, account: &Pubkey, parallel: bool) -> Instruction {
+    let entrypoint = match parallel {
+        true => Entrypoint::ScheduleParallel,
+        false => Entrypoint::Schedule,
+    };
+    instruction(program_id, account, &entrypoint)
+}
+
+/// Schedules every task upfront, from the parameters of the proof.
+pub fn schedule_eager(program_id: &Pubkey, account: &Pubkey) -> Instruction {
+    instruction(program_id, account, &Entrypoint::ScheduleEager)
+}
+
+pub fn verify_proof(program_id: &Pubkey, account: &Pubkey) -> Instruction {
+    instruction(program_id, account, &Entrypoint::VerifyProof)
+}
+
+/// Verifies the `target` table exported by the proof account in the work account, which is the only one written.
+pub fn verify_work(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    work: &Pubkey,
+    target: TableDecommitTarget,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*account, false),
+            AccountMeta::new(*work, false),
+        ],
+        data: Entrypoint::VerifyWork {
+            target: target.into(),
+        }
+        .pack(),
+    }
+}
+
+/// Marks the table of the verified work account as verified in the proof account.
+pub fn merge_work(program_id: &Pubkey, account: &Pubkey, work: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*work, false),
+        ],
+        data: Entrypoint::MergeWork.pack(),
+    }
+}
@@ -0,0 +1,209 @@
+use core::mem::offset_of;
+
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+use swiftness::{TransformTo, parse};
+
+use crate::intermediate::Intermediate;
+use crate::progress::{Progress, progress};
+use crate::task::{TableDecommitTarget, Tasks};
+use crate::verify::stark_verify::table_decommit::parallel::ParallelCache;
+use crate::work::WORK_ACCOUNT_SIZE;
+use crate::{ACCOUNT_HEADER_SIZE, Cache, Felt, ProofAccount, VerificationStage};
+
+pub mod instructions;
+pub mod packing;
+pub mod upload;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Compute units requested for every verification transaction.
+pub const COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Converts a JSON proof into the image of the `ProofAccount`, without the header.
+pub fn prepare(proof_json: &str) -> Result<Vec<u8>> {
+    let proof = parse(proof_json)?.transform_to();
+
+    let mut image = vec![0u8; size_of::<ProofAccount>()];
+    bytemuck::from_bytes_mut::<ProofAccount>(&mut image).proof = proof;
+    Ok(image)
+}
+
+/// Size of the account holding the image, including the header.
+pub fn account_size(image: &[u8]) -> usize {
+    ACCOUNT_HEADER_SIZE + image.len()
+}
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+}
+
+impl Client {
+    pub fn new(rpc: RpcClient, payer: Keypair, program_id: Pubkey) -> Self {
+        Client {
+            rpc,
+            payer,
+            program_id,
+        }
+    }
+
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature> {
+        let mut keypairs = vec![&self.payer];
+        keypairs.extend_from_slice(signers);
+
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &keypairs,
+            blockhash,
+        );
+
+        Ok(self.rpc.send_and_confirm_transaction(&tx).await?)
+    }
+
+    /// Creates a rent exempt account owned by the program, large enough for the image.
+    pub async fn create_account(&self, account: &Keypair, image: &[u8]) -> Result<Signature> {
+        self.create_program_account(account, account_size(image))
+            .await
+    }
+
+    async fn create_program_account(&self, account: &Keypair, size: usize) -> Result<Signature> {
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(size)
+            .await?;
+
+        let ix = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &account.pubkey(),
+            lamports,
+            size as u64,
+            &self.program_id,
+        );
+
+        self.send(&[ix], &[account]).await
+    }
+
+    pub async fn schedule(&self, account: &Pubkey, parallel: bool) -> Result<Signature> {
+        let ixs = [
+            instructions::request_heap_frame(),
+            instructions::schedule(&self.program_id, account, parallel),
+        ];
+        self.send(&ixs, &[]).await
+    }
+
+    pub async fn schedule_eager(&self, account: &Pubkey) -> Result<Signature> {
+        let ixs = [
+            instructions::request_heap_frame(),
+            instructions::schedule_eager(&self.program_id, account),
+        ];
+        self.send(&ixs, &[]).await
+    }
+
+    /// Executes tasks one transaction at a time, until the proof is verified.
+    ///
+    /// Parallel schedules stop before the join while exported tables aren't merged from work accounts,
+    /// returning the `Verify` stage.
+    pub async fn crank(
+        &self,
+        account: &Pubkey,
+        mut on_progress: impl FnMut(&Progress),
+    ) -> Result<VerificationStage> {
+        loop {
+            let data = self.rpc.get_account_data(account).await?;
+            let progress = progress(&data)?;
+            on_progress(&progress);
+
+            match progress.stage {
+                VerificationStage::Publish => return Err("proof is not scheduled".into()),
+                VerificationStage::Verify => {}
+                VerificationStage::Verified => return Ok(VerificationStage::Verified),
+            }
+
+            if let Some(Tasks::TableDecommitJoin) = progress.current {
+                let offset = ACCOUNT_HEADER_SIZE
+                    + offset_of!(ProofAccount, cache)
+                    + offset_of!(Cache, parallel);
+                let parallel: ParallelCache = bytemuck::pod_read_unaligned(
+                    &data[offset..offset + size_of::<ParallelCache>()],
+                );
+                if parallel.exported & !parallel.verified != 0 {
+                    return Ok(VerificationStage::Verify);
+                }
+            }
+
+            let ixs = [
+                ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+                instructions::request_heap_frame(),
+                instructions::verify_proof(&self.program_id, account),
+            ];
+            self.send(&ixs, &[]).await?;
+        }
+    }
+
+    /// Creates a work account and verifies the `target` table exported by the proof account in it,
+    /// one transaction at a time. Returns the address of the work account, to merge it.
+    pub async fn verify_work(
+        &self,
+        account: &Pubkey,
+        target: TableDecommitTarget,
+    ) -> Result<Pubkey> {
+        let work = Keypair::new();
+        self.create_program_account(&work, WORK_ACCOUNT_SIZE)
+            .await?;
+
+        loop {
+            let ixs = [
+                ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+                instructions::request_heap_frame(),
+                instructions::verify_work(&self.program_id, account, &work.pubkey(), target),
+            ];
+            self.send(&ixs, &[]).await?;
+
+            let data = self.rpc.get_account_data(&work.pubkey()).await?;
+            if VerificationStage::try_from(data[0])? == VerificationStage::Verified {
+                return Ok(work.pubkey());
+            }
+        }
+    }
+
+    /// Marks the table of the verified work account as verified in the proof account.
+    pub async fn merge_work(&self, account: &Pubkey, work: &Pubkey) -> Result<Signature> {
+        let ix = instructions::merge_work(&self.program_id, account, work);
+        self.send(&[ix], &[]).await
+    }
+
+    pub async fn progress(&self, account: &Pubkey) -> Result<Progress> {
+        let data = self.rpc.get_account_data(account).await?;
+        Ok(progress(&data)?)
+    }
+
+    /// Program hash and output of the verified proof.
+    pub async fn result(&self, account: &Pubkey) -> Result<(Felt, Vec<Felt>)> {
+        let data = self.rpc.get_account_data(account).await?;
+        if progress(&data)?.stage != VerificationStage::Verified {
+            return Err("proof is not verified".into());
+        }
+
+        let offset = ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, intermediate);
+        let intermediate: Intermediate =
+            bytemuck::pod_read_unaligned(&data[offset..offset + size_of::<Intermediate>()]);
+
+        Ok((intermediate.program_hash(), intermediate.output().to_vec()))
+    }
+}
@@ -0,0 +1,91 @@
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+use super::instructions;
+
+/// Size of the transaction on the wire, once signed by the payer.
+pub fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    bincode::serialized_size(&Transaction::new_unsigned(message)).unwrap() as usize
+}
+
+pub fn fits(payer: &Pubkey, instructions: &[Instruction]) -> bool {
+    transaction_size(payer, instructions) <= PACKET_DATA_SIZE
+}
+
+/// Largest fragment a `PublishFragment` transaction can carry.
+pub fn max_fragment_size(program_id: &Pubkey, payer: &Pubkey, account: &Pubkey) -> usize {
+    let fragment = |size: usize| {
+        let data = vec![0u8; size];
+        [instructions::publish_fragment(
+            program_id, account, 0, &data,
+        )]
+    };
+
+    // The length prefix of the data is variable, so the estimate can be a couple of bytes too large.
+    let overhead = transaction_size(payer, &fragment(0));
+    let mut size = PACKET_DATA_SIZE.saturating_sub(overhead);
+    while size > 0 && !fits(payer, &fragment(size)) {
+        size -= 1;
+    }
+    size
+}
+
+/// Packs the instructions into transactions fitting in a packet, largest instructions first.
+///
+/// Only the last transaction is open, so every instruction is tried once and packing stays linear.
+/// Every item keeps its tag, so the caller knows which instructions landed in which transaction.
+pub fn pack<T>(payer: &Pubkey, mut items: Vec<(T, Instruction)>) -> Vec<Vec<(T, Instruction)>> {
+    items.sort_by_key(|(_, ix)| std::cmp::Reverse(ix.data.len()));
+
+    let mut transactions: Vec<Vec<(T, Instruction)>> = vec![];
+    let mut open: Vec<Instruction> = vec![]; // Instructions of the last transaction.
+    for item in items {
+        open.push(item.1.clone());
+        match transactions.last_mut() {
+            Some(transaction) if fits(payer, &open) => transaction.push(item),
+            _ => {
+                open = vec![item.1.clone()];
+                transactions.push(vec![item]);
+            }
+        }
+    }
+    transactions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fragment_packing() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+
+        let size = max_fragment_size(&program_id, &payer, &account);
+        let fragment = |account: &Pubkey, size: usize| {
+            instructions::publish_fragment(&program_id, account, 0, &vec![0u8; size])
+        };
+        assert!(fits(&payer, &[fragment(&account, size)]));
+        assert!(!fits(&payer, &[fragment(&account, size + 1)]));
+        // At least twice as much as the fixed 500 byte chunks used before.
+        assert!(size >= 1000, "fragment of {size} bytes");
+
+        // A full fragment travels alone, the tails of two other accounts share a transaction.
+        let items = vec![
+            (0, fragment(&account, 200)),
+            (1, fragment(&account, size)),
+            (2, fragment(&Pubkey::new_unique(), 300)),
+        ];
+        let transactions = pack(&payer, items);
+        let tags: Vec<Vec<_>> = transactions
+            .iter()
+            .map(|transaction| transaction.iter().map(|(tag, _)| *tag).collect())
+            .collect();
+        assert_eq!(tags, vec![vec![1], vec![2, 0]]);
+    }
+}
@@ -0,0 +1,362 @@
+use std::path::Path;
+use std::time::Duration;
+
+use futures::StreamExt;
+use solana_program::keccak;
+use solana_rpc_client_api::client_error::{Error as ClientError, ErrorKind};
+use solana_rpc_client_api::request::{RpcError, RpcResponseErrorData};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::TransactionError;
+
+use super::{Client, Result, instructions, packing};
+use crate::ACCOUNT_HEADER_SIZE;
+
+/// Transactions in flight at once.
+pub const MAX_CONCURRENT_TRANSACTIONS: usize = 16;
+
+/// Attempts to land a single transaction before the upload fails.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// Rounds of comparing the account with the image and resending the differences, the account is
+/// compared once more after the last one.
+pub const MAX_ROUNDS: usize = 3;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// Image to be published to an account.
+#[derive(Debug, Clone, Copy)]
+pub struct Upload<'a> {
+    pub account: Pubkey,
+    pub image: &'a [u8],
+    pub state_path: Option<&'a Path>,
+}
+
+/// Progress of an upload, saved after every confirmed fragment.
+///
+/// A resumed upload only sends the fragments the saved state has no confirmation for, the account is
+/// compared with the image after that, so a stale state costs a round but can't skip a fragment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadState {
+    pub account: Pubkey,
+    pub image_hash: [u8; 32],
+    pub chunk_size: usize,
+    pub confirmed: Vec<bool>,
+}
+
+impl UploadState {
+    pub fn new(account: &Pubkey, image: &[u8], chunk_size: usize) -> Self {
+        UploadState {
+            account: *account,
+            image_hash: keccak::hash(image).to_bytes(),
+            chunk_size,
+            confirmed: vec![false; image.len().div_ceil(chunk_size)],
+        }
+    }
+
+    /// Reads the state saved at `path`, `None` if it's missing or belongs to another upload.
+    pub fn load(path: &Path, account: &Pubkey, image: &[u8], chunk_size: usize) -> Option<Self> {
+        let state = Self::from_bytes(&std::fs::read(path).ok()?)?;
+        let fresh = UploadState::new(account, image, chunk_size);
+        let matches = state.account == fresh.account
+            && state.image_hash == fresh.image_hash
+            && state.chunk_size == fresh.chunk_size
+            && state.confirmed.len() == fresh.confirmed.len();
+
+        matches.then_some(state)
+    }
+
+    /// Writes the state next to `path` first, so a crash never leaves it truncated.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.to_bytes())?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// The account, the image hash, the chunk size as a little-endian `u64`, and a byte per fragment.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(72 + self.confirmed.len());
+        bytes.extend_from_slice(self.account.as_ref());
+        bytes.extend_from_slice(&self.image_hash);
+        bytes.extend_from_slice(&(self.chunk_size as u64).to_le_bytes());
+        bytes.extend(self.confirmed.iter().map(|&confirmed| confirmed as u8));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (account, rest) = bytes.split_first_chunk::<32>()?;
+        let (image_hash, rest) = rest.split_first_chunk::<32>()?;
+        let (chunk_size, confirmed) = rest.split_first_chunk::<8>()?;
+
+        Some(UploadState {
+            account: Pubkey::new_from_array(*account),
+            image_hash: *image_hash,
+            chunk_size: u64::from_le_bytes(*chunk_size).try_into().ok()?,
+            confirmed: confirmed
+                .iter()
+                .map(|&byte| match byte {
+                    0 => Some(false),
+                    1 => Some(true),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Marks fragments as confirmed only if the account holds the same bytes as the image.
+    pub fn sync(&mut self, image: &[u8], account_data: &[u8]) {
+        for (i, chunk) in image.chunks(self.chunk_size).enumerate() {
+            let offset = i * self.chunk_size;
+            self.confirmed[i] = account_data.get(offset..offset + chunk.len()) == Some(chunk);
+        }
+    }
+
+    pub fn pending(&self) -> Vec<usize> {
+        (0..self.confirmed.len())
+            .filter(|&i| !self.confirmed[i])
+            .collect()
+    }
+
+    pub fn n_confirmed(&self) -> usize {
+        self.confirmed
+            .iter()
+            .filter(|&&confirmed| confirmed)
+            .count()
+    }
+}
+
+impl Client {
+    /// Largest fragment of an image sent in a single transaction.
+    pub fn fragment_size(&self) -> usize {
+        // All accounts have keys of the same size, so any account gives the same result.
+        packing::max_fragment_size(&self.program_id, &self.payer.pubkey(), &Pubkey::default())
+    }
+
+    /// Publishes the parts of the image that differ from the account, until they match.
+    ///
+    /// With a `state_path` the progress is persisted, an interrupted upload resumes from it and it's
+    /// removed once the upload is done.
+    pub async fn upload(
+        &self,
+        account: &Pubkey,
+        image: &[u8],
+        state_path: Option<&Path>,
+        mut on_progress: impl FnMut(&UploadState),
+    ) -> Result<()> {
+        let upload = Upload {
+            account: *account,
+            image,
+            state_path,
+        };
+        self.upload_many(&[upload], |_, state| on_progress(state))
+            .await
+    }
+
+    /// Publishes multiple images at once, packing fragments of different accounts into shared transactions.
+    pub async fn upload_many(
+        &self,
+        uploads: &[Upload<'_>],
+        mut on_progress: impl FnMut(usize, &UploadState),
+    ) -> Result<()> {
+        let chunk_size = self.fragment_size();
+        let mut resumed = vec![];
+        let mut states = vec![];
+        for upload in uploads {
+            let saved = upload.state_path.and_then(|path| {
+                UploadState::load(path, &upload.account, upload.image, chunk_size)
+            });
+            resumed.push(saved.is_some());
+            states
+                .push(saved.unwrap_or_else(|| {
+                    UploadState::new(&upload.account, upload.image, chunk_size)
+                }));
+        }
+
+        for round in 0..=MAX_ROUNDS {
+            let mut fragments = vec![];
+            for (i, (upload, state)) in uploads.iter().zip(&mut states).enumerate() {
+                let data = self.rpc.get_account_data(&upload.account).await?;
+                if data.len() < ACCOUNT_HEADER_SIZE + upload.image.len() {
+                    return Err(
+                        format!("account {} is too small for the image", upload.account).into(),
+                    );
+                }
+
+                // A resumed upload first sends what the saved state is missing, later rounds what
+                // the account is.
+                if round > 0 || !resumed[i] {
+                    state.sync(upload.image, &data[ACCOUNT_HEADER_SIZE..]);
+                }
+                on_progress(i, state);
+
+                fragments.extend(state.pending().into_iter().map(|chunk| {
+                    let offset = chunk * chunk_size;
+                    let data = &upload.image[offset..upload.image.len().min(offset + chunk_size)];
+                    let ix = instructions::publish_fragment(
+                        &self.program_id,
+                        &upload.account,
+                        offset,
+                        data,
+                    );
+                    ((i, chunk), ix)
+                }));
+            }
+
+            if fragments.is_empty() && (round > 0 || !resumed.contains(&true)) {
+                for path in uploads.iter().filter_map(|upload| upload.state_path) {
+                    if path.exists() {
+                        std::fs::remove_file(path)?;
+                    }
+                }
+                return Ok(());
+            }
+            if round == MAX_ROUNDS {
+                break;
+            }
+
+            let transactions = packing::pack(&self.payer.pubkey(), fragments);
+            let mut sent = futures::stream::iter(transactions)
+                .map(|transaction| async move {
+                    let (tags, ixs): (Vec<_>, Vec<_>) = transaction.into_iter().unzip();
+                    (tags, self.send_with_backoff(&ixs).await)
+                })
+                .buffer_unordered(MAX_CONCURRENT_TRANSACTIONS);
+
+            while let Some((tags, result)) = sent.next().await {
+                result?;
+                for (i, chunk) in tags {
+                    states[i].confirmed[chunk] = true;
+                    if let Some(path) = uploads[i].state_path {
+                        states[i].save(path)?;
+                    }
+                    on_progress(i, &states[i]);
+                }
+            }
+        }
+
+        Err("account data doesn't match the image".into())
+    }
+
+    /// Sends the instructions and waits for confirmation, retrying transient failures with
+    /// exponential backoff.
+    async fn send_with_backoff(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let mut backoff = INITIAL_BACKOFF;
+
+        for _ in 1..MAX_ATTEMPTS {
+            match self.send(instructions, &[]).await {
+                Err(error) if is_transient(&*error) => {
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+
+        self.send(instructions, &[]).await
+    }
+}
+
+/// Whether sending the transaction again can succeed: it didn't reach the node, the node is rate
+/// limiting, or the blockhash expired. Errors of the program would fail the same way again.
+pub fn is_transient(error: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    let Some(error) = error.downcast_ref::<ClientError>() else {
+        return false;
+    };
+
+    match error.kind() {
+        // Includes HTTP errors, like 429 when rate limited.
+        ErrorKind::Io(_) | ErrorKind::Reqwest(_) => true,
+        ErrorKind::TransactionError(error) => *error == TransactionError::BlockhashNotFound,
+        ErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.err == Some(TransactionError::BlockhashNotFound),
+        // The transaction wasn't confirmed before its blockhash expired.
+        ErrorKind::RpcError(RpcError::ForUser(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_file() {
+        let account = Pubkey::new_unique();
+        let image: Vec<u8> = (0..2500u32).map(|i| i as u8).collect();
+        let mut state = UploadState::new(&account, &image, 1000);
+        state.confirmed[1] = true;
+        assert_eq!(
+            UploadState::from_bytes(&state.to_bytes()),
+            Some(state.clone())
+        );
+
+        let path = std::env::temp_dir().join(format!("{account}.upload"));
+        state.save(&path).unwrap();
+        assert_eq!(
+            UploadState::load(&path, &account, &image, 1000),
+            Some(state.clone())
+        );
+        assert_eq!(state.pending(), vec![0, 2]);
+
+        // Saved for another account, image or fragment size.
+        let mut other_image = image.clone();
+        other_image[0] ^= 1;
+        assert_eq!(
+            UploadState::load(&path, &Pubkey::new_unique(), &image, 1000),
+            None
+        );
+        assert_eq!(UploadState::load(&path, &account, &other_image, 1000), None);
+        assert_eq!(UploadState::load(&path, &account, &image, 500), None);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(UploadState::load(&path, &account, &image, 1000), None);
+
+        let bytes = state.to_bytes();
+        assert_eq!(UploadState::from_bytes(&bytes[..71]), None);
+        let mut corrupted = bytes.clone();
+        corrupted[72] = 2;
+        assert_eq!(UploadState::from_bytes(&corrupted), None);
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        use solana_sdk::instruction::InstructionError;
+
+        let error = |kind: ErrorKind| -> Box<dyn std::error::Error + Send + Sync> {
+            Box::new(ClientError::from(kind))
+        };
+
+        let transient = [
+            error(ErrorKind::Io(std::io::ErrorKind::ConnectionReset.into())),
+            error(ErrorKind::TransactionError(
+                TransactionError::BlockhashNotFound,
+            )),
+            error(ErrorKind::RpcError(RpcError::ForUser(
+                "unable to confirm transaction".into(),
+            ))),
+        ];
+        for error in transient {
+            assert!(is_transient(&*error), "{error}");
+        }
+
+        let deterministic = [
+            error(ErrorKind::TransactionError(
+                TransactionError::InstructionError(0, InstructionError::Custom(7)),
+            )),
+            error(ErrorKind::RpcError(RpcError::RpcResponseError {
+                code: -32002,
+                message: "Transaction simulation failed".into(),
+                data: RpcResponseErrorData::Empty,
+            })),
+            "account data doesn't match the image".into(),
+        ];
+        for error in deterministic {
+            assert!(!is_transient(&*error), "{error}");
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use core::alloc::{GlobalAlloc, Layout};
+
+#[cfg(target_os = "solana")]
+use solana_program::entrypoint::HEAP_START_ADDRESS;
+
+/// Heap frame requested by the client, the largest a transaction can request.
+pub const HEAP_FRAME: usize = 256 * 1024;
+
+/// Length of the heap the program allocates from, without `custom-heap` the default frame.
+#[cfg(feature = "custom-heap")]
+pub const HEAP_LENGTH: usize = HEAP_FRAME;
+#[cfg(not(feature = "custom-heap"))]
+pub const HEAP_LENGTH: usize = solana_program::entrypoint::HEAP_LENGTH;
+
+/// Start of the heap which is never allocated: the position of the allocator, then the `panic::Context`.
+pub const HEAP_RESERVED: usize = size_of::<usize>() + size_of::<crate::panic::Context>();
+
+/// Bump allocator which never frees memory, same as the default one of the entrypoint.
+///
+/// The position is stored in the first word of the region, allocating downwards from the end.
+pub struct BumpAllocator {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl BumpAllocator {
+    /// Bytes allocated so far, including the padding.
+    pub fn used(&self) -> usize {
+        // SAFETY: the region starts with the position, zeroed before the first allocation.
+        match unsafe { *(self.start as *const usize) } {
+            0 => 0,
+            position => self.start + self.len - position,
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    #[inline]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let position = self.start as *mut usize;
+        let mut top = match unsafe { *position } {
+            0 => self.start + self.len,
+            top => top,
+        };
+        top = top.saturating_sub(layout.size()) & !(layout.align().wrapping_sub(1));
+        if top < self.start + HEAP_RESERVED {
+            return core::ptr::null_mut();
+        }
+        unsafe { *position = top };
+        top as *mut u8
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, _: *mut u8, _: Layout) {}
+}
+
+#[cfg(all(target_os = "solana", feature = "custom-heap"))]
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator {
+    start: HEAP_START_ADDRESS as usize,
+    len: HEAP_LENGTH,
+};
+
+/// Bytes taken from the heap of the program, by either allocator.
+#[cfg(target_os = "solana")]
+pub fn used() -> usize {
+    BumpAllocator {
+        start: HEAP_START_ADDRESS as usize,
+        len: HEAP_LENGTH,
+    }
+    .used()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_allocator() {
+        // Zeroed like the heap frame, the first word holds the position.
+        let mut region = vec![0u64; 128];
+        let allocator = BumpAllocator {
+            start: region.as_mut_ptr() as usize,
+            len: region.len() * 8,
+        };
+        let end = allocator.start + allocator.len;
+        assert_eq!(allocator.used(), 0);
+
+        unsafe {
+            let a = allocator.alloc(Layout::from_size_align(100, 8).unwrap());
+            assert_eq!(a as usize, end - 104);
+            assert_eq!(allocator.used(), 104);
+
+            let b = allocator.alloc(Layout::from_size_align(1, 1).unwrap());
+            assert_eq!(b as usize, end - 105);
+            allocator.dealloc(b, Layout::from_size_align(1, 1).unwrap());
+            assert_eq!(allocator.used(), 105);
+
+            // Aligned below the previous allocation.
+            let c = allocator.alloc(Layout::from_size_align(8, 32).unwrap());
+            assert_eq!(c as usize % 32, 0);
+            assert!((c as usize) + 8 <= b as usize);
+
+            // Never overwrites the position.
+            let used = allocator.used();
+            let full = allocator.alloc(Layout::from_size_align(allocator.len - used, 1).unwrap());
+            assert!(full.is_null());
+            assert_eq!(allocator.used(), used);
+        }
+        assert_eq!(HEAP_FRAME, 256 * 1024);
+    }
+}
@@ -0,0 +1,227 @@
+use core::mem::offset_of;
+
+use serde_json::{Value, json};
+
+use crate::intermediate::{Intermediate, VerifyOutputIntermediate};
+use crate::schedule::Schedule;
+use crate::task::RawTask;
+use crate::{
+    ACCOUNT_HEADER_SIZE, AccountHeader, Cache, Felt, PROGRAM_ID, ProofAccount, StarkProof,
+};
+
+/// Capacity of the program output.
+const OUTPUT_CAPACITY: usize = 1024;
+
+/// Where the IDL is committed, relative to the root of the crate.
+pub const PATH: &str = "idl/solana_verifier.json";
+
+/// The IDL as committed, pretty-printed with a trailing newline.
+pub fn render() -> String {
+    serde_json::to_string_pretty(&idl()).unwrap() + "\n"
+}
+
+/// Describes the instructions and the account layout in the Anchor IDL format.
+///
+/// Anchor has no notion of offsets, so they're listed in the additional `layout` section,
+/// relative to the start of the account data.
+pub fn idl() -> Value {
+    let proof_account = |offset: usize| ACCOUNT_HEADER_SIZE + offset;
+    let intermediate = proof_account(offset_of!(ProofAccount, intermediate));
+    let verify_output = intermediate + offset_of!(Intermediate, verify_output);
+    let output = verify_output + offset_of!(VerifyOutputIntermediate, output);
+    let (output_data, output_len, output_len_size) = output_layout();
+
+    json!({
+        "address": PROGRAM_ID,
+        "metadata": {
+            "name": "solana_verifier",
+            "version": env!("CARGO_PKG_VERSION"),
+            "spec": "0.1.0",
+            "description": "Verifier of Cairo STARK proofs, split into tasks fitting in a transaction",
+        },
+        "instructions": [
+            instruction("publish_fragment", 0, &[("proof", true)], json!([
+                { "name": "offset", "type": "u32" },
+                {
+                    "name": "data",
+                    "docs": ["Until the end of the instruction, without a length prefix."],
+                    "type": "bytes",
+                },
+            ])),
+            instruction("schedule", 1, &[("proof", true)], json!([])),
+            instruction("verify_proof", 2, &[("proof", true)], json!([])),
+            instruction("schedule_parallel", 3, &[("proof", true)], json!([])),
+            instruction("verify_work", 4, &[("proof", false), ("work", true)], json!([
+                {
+                    "name": "target",
+                    "docs": ["Table verified by the work account, as `TableDecommitTarget` variant and FRI layer."],
+                    "type": { "array": ["u8", 2] },
+                },
+            ])),
+            instruction("merge_work", 5, &[("proof", true), ("work", true)], json!([])),
+            instruction("schedule_eager", 6, &[("proof", true)], json!([])),
+        ],
+        "accounts": [
+            { "name": "ProofAccount", "discriminator": [] },
+        ],
+        "types": [
+            {
+                "name": "ProofAccount",
+                "docs": ["Follows the `AccountHeader`, see `layout` for the fields read by clients."],
+                "serialization": "bytemuck",
+                "repr": { "kind": "c" },
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        bytes("proof", size_of::<StarkProof>()),
+                        bytes("cache", size_of::<Cache>()),
+                        bytes("intermediate", size_of::<Intermediate>()),
+                        bytes("schedule", size_of::<Schedule<RawTask, 1000>>()),
+                    ],
+                },
+            },
+            {
+                "name": "AccountHeader",
+                "serialization": "bytemuck",
+                "repr": { "kind": "c" },
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "stage", "type": "u8" },
+                        { "name": "kind", "type": defined("AccountKind") },
+                        { "name": "executed", "type": "u16" },
+                        { "name": "planned", "type": "u16" },
+                        { "name": "_reserved", "type": { "array": ["u8", 2] } },
+                    ],
+                },
+            },
+            {
+                "name": "VerificationStage",
+                "repr": { "kind": "rust", "modifiers": ["u8"] },
+                "type": {
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Publish" },
+                        { "name": "Verify" },
+                        { "name": "Verified" },
+                    ],
+                },
+            },
+            {
+                "name": "AccountKind",
+                "docs": ["Set by the program, proof and work accounts can't be passed as the other."],
+                "repr": { "kind": "rust", "modifiers": ["u8"] },
+                "type": {
+                    "kind": "enum",
+                    "variants": [
+                        { "name": "Uninitialized" },
+                        { "name": "Proof" },
+                        { "name": "Work" },
+                    ],
+                },
+            },
+            {
+                "name": "Felt",
+                "docs": ["In-memory `starknet_types_core::felt::Felt`, in Montgomery form."],
+                "type": { "kind": "type", "alias": { "array": ["u8", size_of::<Felt>()] } },
+            },
+        ],
+        "layout": {
+            "size": ACCOUNT_HEADER_SIZE + size_of::<ProofAccount>(),
+            "fields": [
+                field("stage", offset_of!(AccountHeader, stage), 1, defined("VerificationStage")),
+                field("kind", offset_of!(AccountHeader, kind), 1, defined("AccountKind")),
+                field("executed", offset_of!(AccountHeader, executed), 2, json!("u16")),
+                field("planned", offset_of!(AccountHeader, planned), 2, json!("u16")),
+                field(
+                    "proof",
+                    proof_account(offset_of!(ProofAccount, proof)),
+                    size_of::<StarkProof>(),
+                    json!("bytes"),
+                ),
+                field(
+                    "intermediate",
+                    intermediate,
+                    size_of::<Intermediate>(),
+                    json!("bytes"),
+                ),
+                field(
+                    "schedule",
+                    proof_account(offset_of!(ProofAccount, schedule)),
+                    size_of::<Schedule<RawTask, 1000>>(),
+                    json!("bytes"),
+                ),
+                field(
+                    "program_hash",
+                    verify_output + offset_of!(VerifyOutputIntermediate, program_hash),
+                    size_of::<Felt>(),
+                    defined("Felt"),
+                ),
+                field(
+                    "output",
+                    output + output_data,
+                    OUTPUT_CAPACITY * size_of::<Felt>(),
+                    json!({ "array": [defined("Felt"), OUTPUT_CAPACITY] }),
+                ),
+                field(
+                    "output_len",
+                    output + output_len,
+                    output_len_size,
+                    json!(format!("u{}", output_len_size * 8)),
+                ),
+            ],
+        },
+    })
+}
+
+fn instruction(name: &str, discriminator: u8, accounts: &[(&str, bool)], args: Value) -> Value {
+    let accounts: Vec<Value> = accounts
+        .iter()
+        .map(|(name, writable)| json!({ "name": name, "writable": writable }))
+        .collect();
+
+    json!({
+        "name": name,
+        "discriminator": [discriminator],
+        "accounts": accounts,
+        "args": args,
+    })
+}
+
+fn defined(name: &str) -> Value {
+    json!({ "defined": { "name": name } })
+}
+
+fn bytes(name: &str, size: usize) -> Value {
+    json!({ "name": name, "type": { "array": ["u8", size] } })
+}
+
+fn field(name: &str, offset: usize, size: usize, ty: Value) -> Value {
+    json!({ "name": name, "offset": offset, "size": size, "type": ty })
+}
+
+/// Offsets of the elements and of the length within the output, and the size of the length.
+///
+/// `FunVec` keeps its fields private, so the length is found by pushing an element and
+/// looking for the bytes that changed.
+fn output_layout() -> (usize, usize, usize) {
+    let empty = Intermediate::default();
+    let mut pushed = empty;
+    pushed.verify_output.output.push(Felt::ZERO);
+
+    let start =
+        offset_of!(Intermediate, verify_output) + offset_of!(VerifyOutputIntermediate, output);
+    let size = size_of_val(&empty.verify_output.output);
+    let (empty, pushed) = (
+        &bytemuck::bytes_of(&empty)[start..start + size],
+        &bytemuck::bytes_of(&pushed)[start..start + size],
+    );
+
+    let len_size = size - OUTPUT_CAPACITY * size_of::<Felt>();
+    let len = (0..size)
+        .find(|&i| empty[i] != pushed[i])
+        .expect("output length not found");
+    let data = if len == 0 { len_size } else { 0 };
+
+    (data, len, len_size)
+}
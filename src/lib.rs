@@ -1,32 +1,113 @@
 use bytemuck::{Pod, Zeroable};
 use intermediate::Intermediate;
 use schedule::Schedule;
-use serde::{Deserialize, Serialize};
 use solana_program::account_info::next_account_info;
-use solana_program::entrypoint;
 use solana_program::program_error::ProgramError;
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey};
 
 pub use swiftness_stark::types::{Felt, LegacyCache, StarkProof};
 use task::{RawTask, Tasks};
-use verify::stark_verify::table_decommit::TableDecommitCache;
+use verify::stark_verify::table_decommit::parallel::ParallelCache;
+use verify::stark_verify::table_decommit::{TableDecommitCache, TableDecommitTarget};
+use work::WORK_ACCOUNT_SIZE;
 
+#[cfg(feature = "client")]
+pub mod client;
+pub mod heap;
+#[cfg(feature = "host")]
+pub mod idl;
 pub mod intermediate;
+pub mod panic;
+pub mod progress;
 pub mod schedule;
 pub mod task;
 mod verify;
+pub mod work;
 
 // declare and export the program's entrypoint
-entrypoint!(process_instruction_data);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction_data);
 
 pub const PROGRAM_ID: &str = "ANH87aBZFKHhB3aLAndnp8cJd8QNL58buSeLCtVb1ukj";
 
-#[repr(u8)]
-#[derive(Serialize, Deserialize)]
+/// Instructions of the program.
+///
+/// Encoded as a 1 byte discriminator followed by the fields, integers are little-endian:
+///
+/// | Instruction        | Discriminator | Fields                                        |
+/// |--------------------|---------------|-----------------------------------------------|
+/// | `PublishFragment`  | 0             | `offset: u32`, data until the end             |
+/// | `Schedule`         | 1             |                                               |
+/// | `VerifyProof`      | 2             |                                               |
+/// | `ScheduleParallel` | 3             |                                               |
+/// | `VerifyWork`       | 4             | `target: [u8; 2]`, a `TableDecommitTarget`    |
+/// | `MergeWork`        | 5             |                                               |
+/// | `ScheduleEager`    | 6             |                                               |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Entrypoint<'a> {
-    PublishFragment { offset: usize, data: &'a [u8] },
+    PublishFragment {
+        offset: u32,
+        data: &'a [u8],
+    },
     Schedule,
     VerifyProof,
+    /// Same as `Schedule`, but table decommitments are left to work accounts.
+    ScheduleParallel,
+    /// Accounts: proof account (read-only), work account.
+    VerifyWork {
+        target: [u8; 2],
+    },
+    /// Accounts: proof account, work account.
+    MergeWork,
+    /// Same as `Schedule`, but all the tasks are scheduled upfront, so their number is known.
+    ScheduleEager,
+}
+
+impl<'a> Entrypoint<'a> {
+    pub fn pack(&self) -> Vec<u8> {
+        match self {
+            Entrypoint::PublishFragment { offset, data } => {
+                let mut packed = Vec::with_capacity(5 + data.len());
+                packed.push(0);
+                packed.extend_from_slice(&offset.to_le_bytes());
+                packed.extend_from_slice(data);
+                packed
+            }
+            Entrypoint::Schedule => vec![1],
+            Entrypoint::VerifyProof => vec![2],
+            Entrypoint::ScheduleParallel => vec![3],
+            Entrypoint::VerifyWork { target } => vec![4, target[0], target[1]],
+            Entrypoint::MergeWork => vec![5],
+            Entrypoint::ScheduleEager => vec![6],
+        }
+    }
+
+    pub fn unpack(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let (&discriminator, rest) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        let instruction = match (discriminator, rest) {
+            (0, rest) if rest.len() >= 4 => {
+                let (offset, data) = rest.split_at(4);
+                Entrypoint::PublishFragment {
+                    offset: u32::from_le_bytes(offset.try_into().unwrap()),
+                    data,
+                }
+            }
+            (1, []) => Entrypoint::Schedule,
+            (2, []) => Entrypoint::VerifyProof,
+            (3, []) => Entrypoint::ScheduleParallel,
+            (4, &[variant, fri]) => Entrypoint::VerifyWork {
+                target: [variant, fri],
+            },
+            (5, []) => Entrypoint::MergeWork,
+            (6, []) => Entrypoint::ScheduleEager,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(instruction)
+    }
 }
 
 #[derive(Clone, Copy, Default, Zeroable, Pod, Debug, PartialEq)]
@@ -38,7 +119,9 @@ pub struct ProofAccount {
     pub schedule: Schedule<RawTask, 1000>, // Tasks remaining to be executed.
 }
 
+#[cfg(feature = "host")]
 impl ProofAccount {
+    /// Schedules and verifies the proof locally, returns the number of executed tasks.
     pub fn flow(&mut self) -> usize {
         let account_data = bytemuck::bytes_of_mut(self);
         let mut stage = VerificationStage::Publish;
@@ -59,6 +142,60 @@ impl ProofAccount {
 pub struct Cache {
     pub legacy: LegacyCache,
     pub table: TableDecommitCache,
+    pub parallel: ParallelCache,
+}
+
+/// First bytes of the account, before the 8 byte aligned `ProofAccount`.
+#[derive(Debug, Clone, Copy, Default, Zeroable, Pod, PartialEq)]
+#[repr(C)]
+pub struct AccountHeader {
+    pub stage: u8,
+    pub kind: u8,      // `AccountKind::Proof` once the program wrote to it.
+    pub executed: u16, // Tasks executed so far.
+    pub planned: u16,  // Tasks executed and remaining in the schedule, grows as tasks are split.
+    _reserved: [u8; 2],
+}
+
+pub const ACCOUNT_HEADER_SIZE: usize = size_of::<AccountHeader>();
+
+pub const PROOF_ACCOUNT_SIZE: usize = ACCOUNT_HEADER_SIZE + size_of::<ProofAccount>();
+
+/// What the account holds, the byte after the stage of both proof and work accounts, so one can't be
+/// passed as the other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AccountKind {
+    #[default]
+    Uninitialized = 0,
+    Proof = 1,
+    Work = 2,
+}
+
+impl TryFrom<u8> for AccountKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AccountKind::Uninitialized),
+            1 => Ok(AccountKind::Proof),
+            2 => Ok(AccountKind::Work),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Stage and kind from the first two bytes of an account of `size` bytes.
+fn account_prefix(
+    data: &[u8],
+    size: usize,
+) -> Result<(VerificationStage, AccountKind), ProgramError> {
+    if data.len() != size {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok((
+        VerificationStage::try_from(data[0])?,
+        AccountKind::try_from(data[1])?,
+    ))
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -84,20 +221,121 @@ impl TryFrom<u8> for VerificationStage {
 }
 
 pub fn process_instruction_data(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     account_info: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction: Entrypoint = bincode::deserialize(instruction_data).unwrap();
+    let instruction = Entrypoint::unpack(instruction_data)?;
     let accounts_iter: &mut std::slice::Iter<'_, AccountInfo<'_>> = &mut account_info.iter();
-    let account = next_account_info(accounts_iter).unwrap();
+    let account = next_account_info(accounts_iter)?;
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    match instruction {
+        Entrypoint::VerifyWork { target } => {
+            let work = next_account_info(accounts_iter)?;
+            if work.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            let account_data = account.try_borrow_data()?;
+            let (stage, kind) = account_prefix(&account_data, PROOF_ACCOUNT_SIZE)?;
+            if kind != AccountKind::Proof {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if stage != VerificationStage::Verify {
+                return Err(ProgramError::Custom(9));
+            }
+
+            let mut work_data = work.try_borrow_mut_data()?;
+            let (work_stage, work_kind) = account_prefix(&work_data, WORK_ACCOUNT_SIZE)?;
+            // Fresh accounts become work accounts on the first call.
+            if !matches!(
+                (work_kind, work_stage),
+                (AccountKind::Work, _) | (AccountKind::Uninitialized, VerificationStage::Publish)
+            ) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let target = TableDecommitTarget::try_from(target)?;
+
+            #[cfg(all(target_os = "solana", feature = "custom-panic"))]
+            panic::set_context(panic::Context::new(
+                Tasks::TableDecommit(target).into(),
+                work_stage,
+            ));
+
+            let work_stage = work::verify_work(
+                account.key,
+                &account_data[8..],
+                &mut work_data[8..],
+                work_stage,
+                target,
+            )?;
+            work_data[0] = work_stage as u8;
+            work_data[1] = AccountKind::Work as u8;
+        }
+
+        Entrypoint::MergeWork => {
+            let work = next_account_info(accounts_iter)?;
+            if work.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            let mut account_data = account.try_borrow_mut_data()?;
+            let (stage, kind) = account_prefix(&account_data, PROOF_ACCOUNT_SIZE)?;
+            if kind != AccountKind::Proof {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if stage != VerificationStage::Verify {
+                return Err(ProgramError::Custom(9));
+            }
 
-    let mut account_data = account.try_borrow_mut_data()?;
-    let mut stage = VerificationStage::try_from(account_data[0])?;
+            let mut work_data = work.try_borrow_mut_data()?;
+            let (work_stage, work_kind) = account_prefix(&work_data, WORK_ACCOUNT_SIZE)?;
+            if work_kind != AccountKind::Work {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let work_stage = work::merge_work(
+                account.key,
+                &mut account_data[8..],
+                &mut work_data[8..],
+                work_stage,
+            )?;
+            work_data[0] = work_stage as u8;
+        }
+
+        instruction => {
+            let mut account_data = account.try_borrow_mut_data()?;
+            let (_, kind) = account_prefix(&account_data, PROOF_ACCOUNT_SIZE)?;
+            if kind == AccountKind::Work {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let (header, account_data) = account_data.split_at_mut(ACCOUNT_HEADER_SIZE);
+            let header = bytemuck::from_bytes_mut::<AccountHeader>(header);
+            let stage = VerificationStage::try_from(header.stage)?;
 
-    // Skipping the first byte as stage, and 7 as padding to get the correct alignment.
-    stage = process_instruction(instruction, &mut account_data[8..], stage)?;
-    account_data[0] = stage as u8;
+            let executed = match instruction {
+                Entrypoint::Schedule | Entrypoint::ScheduleParallel | Entrypoint::ScheduleEager => {
+                    Some(0)
+                }
+                Entrypoint::VerifyProof => Some(header.executed.saturating_add(1)),
+                _ => None,
+            };
+
+            header.stage = process_instruction(instruction, account_data, stage)? as u8;
+            header.kind = AccountKind::Proof as u8;
+
+            if let Some(executed) = executed {
+                let remaining = bytemuck::from_bytes::<ProofAccount>(account_data)
+                    .schedule
+                    .remaining();
+                header.executed = executed;
+                header.planned = executed.saturating_add(remaining.try_into().unwrap_or(u16::MAX));
+            }
+        }
+    }
 
     Ok(())
 }
@@ -114,12 +352,17 @@ pub fn process_instruction(
                 return Err(ProgramError::Custom(7));
             }
 
-            account_data[offset..offset + data.len()].copy_from_slice(data);
+            let offset = offset as usize;
+            let fragment = offset
+                .checked_add(data.len())
+                .and_then(|end| account_data.get_mut(offset..end))
+                .ok_or(ProgramError::AccountDataTooSmall)?;
+            fragment.copy_from_slice(data);
             msg!("PublishFragment");
             VerificationStage::Publish
         }
 
-        Entrypoint::Schedule => {
+        Entrypoint::Schedule | Entrypoint::ScheduleParallel | Entrypoint::ScheduleEager => {
             if stage != VerificationStage::Publish {
                 return Err(ProgramError::Custom(8));
             }
@@ -127,10 +370,30 @@ pub fn process_instruction(
             msg!("Schedule");
 
             let proof_account = bytemuck::from_bytes_mut::<ProofAccount>(account_data);
+            let parallel = matches!(instruction, Entrypoint::ScheduleParallel);
+            // Fragments can overwrite the whole account, tasks start from a clean state regardless.
+            bytemuck::bytes_of_mut(&mut proof_account.cache).fill(0);
+            bytemuck::bytes_of_mut(&mut proof_account.intermediate).fill(0);
+            proof_account.cache.parallel.reset(parallel);
             proof_account.schedule.flush();
-            proof_account
-                .schedule
-                .push(Tasks::VerifyProofWithoutStark.into());
+            if parallel {
+                proof_account.schedule.push(Tasks::TableDecommitJoin.into());
+            }
+            if matches!(instruction, Entrypoint::ScheduleEager) {
+                // The number of tasks comes from the parameters, which are otherwise validated by the first task.
+                if !verify::is_supported_layout(&proof_account.proof) {
+                    return Err(ProgramError::Custom(19));
+                }
+                verify::validate_config(&proof_account.proof)
+                    .map_err(|_| ProgramError::Custom(19))?;
+                proof_account
+                    .schedule
+                    .generate_tasks(&proof_account.proof, parallel)?;
+            } else {
+                proof_account
+                    .schedule
+                    .push(Tasks::VerifyProofWithoutStark.into());
+            }
 
             VerificationStage::Verify
         }
@@ -155,19 +418,22 @@ pub fn process_instruction(
                 return Ok(VerificationStage::Verified);
             };
 
+            #[cfg(all(target_os = "solana", feature = "custom-panic"))]
+            panic::set_context(panic::Context::new(task, stage));
+
             let task = Tasks::try_from(&task)?;
             // let task_name = format!("{:?}", task);
             // msg!("Executing task: {}", task_name);
 
-            let mut task = task.view(proof, cache, intermediate);
-            let children = task.execute();
-            schedule.push_slice(
-                &children
-                    .into_iter()
-                    .map(From::from)
-                    .rev()
-                    .collect::<Vec<_>>(),
-            );
+            task.view(proof, cache, intermediate)?.execute();
+
+            #[cfg(all(target_os = "solana", feature = "cu-report"))]
+            msg!("heap used: {}", heap::used());
+
+            // Eager schedules already hold the children.
+            if !schedule.is_eager() {
+                schedule.push_children(task, proof, cache, intermediate);
+            }
 
             if schedule.finished() {
                 VerificationStage::Verified
@@ -175,6 +441,10 @@ pub fn process_instruction(
                 VerificationStage::Verify
             }
         }
+
+        Entrypoint::VerifyWork { .. } | Entrypoint::MergeWork => {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
     };
 
     Ok(stage_after)
@@ -185,42 +455,91 @@ mod tests {
     use super::*;
     use swiftness::{TransformTo, parse};
 
+    /// Image of the proof account with the JSON proof, and the rest zeroed.
+    fn account_image(json: &str) -> Vec<u8> {
+        let mut account = vec![0u8; size_of::<ProofAccount>()];
+        bytemuck::from_bytes_mut::<ProofAccount>(&mut account).proof =
+            parse(json).unwrap().transform_to();
+        account
+    }
+
     pub fn read_proof_from_file() -> Vec<u8> {
-        let account_data = include_bytes!("../resources/proof.bin").to_vec();
-        let account = bytemuck::from_bytes::<ProofAccount>(&account_data);
-        bytemuck::bytes_of(account).to_vec()
+        account_image(include_str!("../resources/saya.json"))
     }
 
-    #[ignore]
-    #[test]
-    pub fn prepare_account() {
-        let small_json = include_str!("../resources/saya.json");
-        let stark_proof = parse(small_json).unwrap();
-        let proof = stark_proof.transform_to();
-
-        let proof_account = ProofAccount {
-            proof,
-            ..Default::default()
-        };
-        let account_data = bytemuck::bytes_of(&proof_account);
+    /// JSON proofs in `resources`, each with the expected result in a `.expected` file next to it.
+    fn fixtures() -> Vec<std::path::PathBuf> {
+        let mut fixtures: Vec<_> = std::fs::read_dir("resources")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json")
+            })
+            .collect();
+        fixtures.sort();
+        fixtures
+    }
+
+    /// Program hash and output of the verified proof, or `None` if it's rejected.
+    type Verdict = Option<(Felt, Vec<Felt>)>;
+
+    fn verify_image(account_data: &mut [u8]) -> Verdict {
+        failing_task(account_data).is_none().then(|| {
+            let ProofAccount { intermediate, .. } =
+                bytemuck::from_bytes::<ProofAccount>(account_data);
+            (intermediate.program_hash(), intermediate.output().to_vec())
+        })
+    }
+
+    fn render_verdict(verdict: &Verdict) -> String {
+        match verdict {
+            Some((program_hash, output)) => {
+                format!("program_hash: {program_hash}\noutput: {output:?}\n")
+            }
+            None => "rejected\n".to_string(),
+        }
+    }
+
+    /// Same as `ProofAccount::flow`, but records the executed tasks and the Keccak of
+    /// `Intermediate` after each of them.
+    fn trace(account_data: &mut [u8]) -> Vec<(Tasks, [u8; 32])> {
+        use sha3::{Digest, Keccak256};
+
+        let mut stage = VerificationStage::Publish;
+        stage = process_instruction(Entrypoint::Schedule, account_data, stage).unwrap();
 
-        let account_data_path = "resources/proof.bin";
+        let mut trace = Vec::new();
+        while stage != VerificationStage::Verified {
+            let ProofAccount { schedule, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+            let task = Tasks::try_from(&schedule.peek().unwrap()).unwrap();
+            stage = process_instruction(Entrypoint::VerifyProof, account_data, stage).unwrap();
 
-        std::fs::write(account_data_path, account_data).unwrap();
-        let account_data = std::fs::read(account_data_path).unwrap();
-        let read_proof_account = bytemuck::from_bytes::<ProofAccount>(&account_data);
+            let ProofAccount { intermediate, .. } =
+                bytemuck::from_bytes::<ProofAccount>(account_data);
+            trace.push((
+                task,
+                Keccak256::digest(bytemuck::bytes_of(intermediate)).into(),
+            ));
+        }
 
-        assert_eq!(&proof_account, read_proof_account);
+        trace
     }
 
     #[test]
     fn test_verify_proof() {
         let account_data = &mut read_proof_from_file()[..];
 
-        let proof_account = bytemuck::from_bytes_mut::<ProofAccount>(account_data);
-        let c = proof_account.flow();
+        let trace = trace(account_data);
+        let merkle_steps = trace
+            .iter()
+            .filter(|(task, _)| matches!(task, Tasks::TableDecommitMerkle(_)))
+            .count();
 
-        assert_eq!(c, 190);
+        // `ComputeNextInner` for each of the 16 cosets in every FRI layer.
+        assert_eq!(trace.len() - merkle_steps, 198);
+        // At least one step for each of the 3 trace tables and 8 inner FRI layers.
+        assert!(merkle_steps >= 11);
 
         let ProofAccount { intermediate, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
 
@@ -233,4 +552,763 @@ mod tests {
             "[0x1, 0x4, 0x193641eb151b0f41674641089952e60bc3aded26e3cf42793655c562b8c3aa0, 0x5ab580b04e3532b6b18f81cfa654a05e29dd8e2352d88df1e765a84072db07, 0xb2c58e4eec9b5a8f0c5ba4d15ae59c8ac8a8d96fca443dd591296ba3391aaf]"
         );
     }
+
+    #[test]
+    fn test_eager_schedule() {
+        use sha3::{Digest, Keccak256};
+
+        let lazy = trace(&mut read_proof_from_file());
+
+        let account_data = &mut read_proof_from_file()[..];
+        let mut stage = VerificationStage::Publish;
+        stage = process_instruction(Entrypoint::ScheduleEager, account_data, stage).unwrap();
+
+        let ProofAccount { schedule, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+        let planned = schedule.remaining();
+        assert!(planned >= lazy.len());
+
+        // Same tasks as scheduled lazily, all known before the first one is executed, plus the
+        // inner FRI and Merkle tasks scheduled for the worst case, which repeat the previous one
+        // without changing the state.
+        let mut matched = 0;
+        for executed in 0..planned {
+            let ProofAccount {
+                schedule,
+                intermediate,
+                ..
+            } = bytemuck::from_bytes::<ProofAccount>(account_data);
+            assert_eq!(schedule.remaining(), planned - executed);
+            let task = Tasks::try_from(&schedule.peek().unwrap()).unwrap();
+            let before: [u8; 32] = Keccak256::digest(bytemuck::bytes_of(intermediate)).into();
+
+            stage = process_instruction(Entrypoint::VerifyProof, account_data, stage).unwrap();
+            let ProofAccount { intermediate, .. } =
+                bytemuck::from_bytes::<ProofAccount>(account_data);
+            let after: [u8; 32] = Keccak256::digest(bytemuck::bytes_of(intermediate)).into();
+
+            if lazy.get(matched) == Some(&(task, after)) {
+                matched += 1;
+            } else {
+                assert!(
+                    matches!(
+                        task,
+                        Tasks::ComputeNextInner(_) | Tasks::TableDecommitMerkle(_)
+                    ),
+                    "{task:?} isn't scheduled lazily"
+                );
+                assert_eq!(Some(&task), lazy.get(matched - 1).map(|(task, _)| task));
+                assert_eq!(before, after, "padding {task:?} changed the state");
+            }
+        }
+        assert_eq!(matched, lazy.len());
+        assert_eq!(stage, VerificationStage::Verified);
+
+        let ProofAccount { intermediate, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+        assert_eq!(
+            intermediate.program_hash().to_string(),
+            "2600195635685626119055100741094371725887213141003183770434823435664529167464"
+        );
+    }
+
+    #[test]
+    fn test_task_trace() {
+        let trace: String = trace(&mut read_proof_from_file())
+            .iter()
+            .map(|(task, hash)| {
+                let hash: String = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+                format!("{task:?} {hash}\n")
+            })
+            .collect();
+
+        // The snapshot is only rewritten when `BLESS` is set, so changes show up in review.
+        let path = "resources/saya.trace";
+        if std::env::var_os("BLESS").is_some() {
+            std::fs::write(path, &trace).unwrap();
+        }
+        let expected = std::fs::read_to_string(path).unwrap_or_else(|_| {
+            panic!("{path} is missing, record it with BLESS=1 cargo test test_task_trace")
+        });
+
+        let mismatch = trace
+            .lines()
+            .zip(expected.lines())
+            .position(|(line, expected)| line != expected);
+        if let Some(i) = mismatch {
+            panic!(
+                "task {i} differs from {path}, run the tests with BLESS=1 to update it\n\
+                 expected: {}\n     got: {}",
+                expected.lines().nth(i).unwrap(),
+                trace.lines().nth(i).unwrap(),
+            );
+        }
+        assert_eq!(
+            trace.lines().count(),
+            expected.lines().count(),
+            "number of tasks differs from {path}, run the tests with BLESS=1 to update it"
+        );
+    }
+
+    #[test]
+    fn test_verify_proof_parallel() {
+        use solana_program::pubkey::Pubkey;
+        use work::{WorkAccount, merge_work, verify_work};
+
+        let account_data = &mut read_proof_from_file()[..];
+        let proof_key = Pubkey::new_unique();
+
+        let mut stage = VerificationStage::Publish;
+        stage = process_instruction(Entrypoint::ScheduleParallel, account_data, stage).unwrap();
+
+        // Everything except the Merkle trees of the trace and composition tables runs in the proof account.
+        loop {
+            let ProofAccount { schedule, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+            if let Some(Tasks::TableDecommitJoin) =
+                schedule.peek().map(|t| Tasks::try_from(&t).unwrap())
+            {
+                break;
+            }
+            stage = process_instruction(Entrypoint::VerifyProof, account_data, stage).unwrap();
+        }
+
+        let targets = [
+            TableDecommitTarget::Original,
+            TableDecommitTarget::Interaction,
+            TableDecommitTarget::Composition,
+        ];
+
+        // FRI layers are verified in the proof account.
+        let mut work_data = vec![0u8; size_of::<WorkAccount>()];
+        let fri = verify_work(
+            &proof_key,
+            account_data,
+            &mut work_data,
+            VerificationStage::Publish,
+            TableDecommitTarget::Fri(0),
+        );
+        assert_eq!(fri, Err(ProgramError::Custom(11)));
+
+        for target in targets {
+            let mut work_data = vec![0u8; size_of::<WorkAccount>()];
+            let mut work_stage = VerificationStage::Publish;
+            while work_stage != VerificationStage::Verified {
+                work_stage =
+                    verify_work(&proof_key, account_data, &mut work_data, work_stage, target)
+                        .unwrap();
+            }
+            merge_work(&proof_key, account_data, &mut work_data, work_stage).unwrap();
+        }
+
+        stage = process_instruction(Entrypoint::VerifyProof, account_data, stage).unwrap();
+        assert_eq!(stage, VerificationStage::Verified);
+
+        let ProofAccount { intermediate, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+        assert_eq!(
+            intermediate.program_hash().to_string(),
+            "2600195635685626119055100741094371725887213141003183770434823435664529167464"
+        );
+    }
+
+    #[test]
+    fn test_progress() {
+        use progress::progress;
+        use solana_program::pubkey::Pubkey;
+
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0u8; ACCOUNT_HEADER_SIZE];
+        data.extend(read_proof_from_file());
+
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+        let accounts = [account];
+        let send = |instruction: Entrypoint| {
+            let instruction_data = instruction.pack();
+            process_instruction_data(&program_id, &accounts, &instruction_data).unwrap();
+        };
+
+        send(Entrypoint::Schedule);
+        assert_eq!(accounts[0].data.borrow()[1], AccountKind::Proof as u8);
+        let p = progress(&accounts[0].data.borrow()).unwrap();
+        assert_eq!(
+            (p.stage, p.executed, p.remaining),
+            (VerificationStage::Verify, 0, 1)
+        );
+        assert!(matches!(p.current, Some(Tasks::VerifyProofWithoutStark)));
+
+        let mut executed = 0;
+        while progress(&accounts[0].data.borrow()).unwrap().stage != VerificationStage::Verified {
+            send(Entrypoint::VerifyProof);
+            executed += 1;
+
+            let p = progress(&accounts[0].data.borrow()).unwrap();
+            assert_eq!(p.executed, executed);
+            assert!(p.percentage() <= 100.0);
+        }
+
+        let p = progress(&accounts[0].data.borrow()).unwrap();
+        assert_eq!(p.remaining, 0);
+        assert!(p.current.is_none());
+        assert_eq!(p.percentage(), 100.0);
+    }
+
+    #[test]
+    fn test_account_owner() {
+        use solana_program::pubkey::Pubkey;
+
+        let program_id = Pubkey::new_unique();
+        let other_program = Pubkey::new_unique();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        // Accounts of another program are rejected before their data is read.
+        let cases = [
+            (Entrypoint::Schedule, [other_program, program_id]),
+            (Entrypoint::VerifyProof, [other_program, program_id]),
+            (
+                Entrypoint::VerifyWork {
+                    target: TableDecommitTarget::Original.into(),
+                },
+                [other_program, program_id],
+            ),
+            (
+                Entrypoint::VerifyWork {
+                    target: TableDecommitTarget::Original.into(),
+                },
+                [program_id, other_program],
+            ),
+            (Entrypoint::MergeWork, [other_program, program_id]),
+            (Entrypoint::MergeWork, [program_id, other_program]),
+        ];
+        for (instruction, owners) in cases {
+            let mut lamports = [0, 0];
+            let mut proof_data = vec![0u8; ACCOUNT_HEADER_SIZE + size_of::<ProofAccount>()];
+            let mut work_data = vec![0u8; 8 + size_of::<work::WorkAccount>()];
+            let [proof_lamports, work_lamports] = &mut lamports;
+            let accounts = [
+                AccountInfo::new(
+                    &keys[0],
+                    false,
+                    true,
+                    proof_lamports,
+                    &mut proof_data,
+                    &owners[0],
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &keys[1],
+                    false,
+                    true,
+                    work_lamports,
+                    &mut work_data,
+                    &owners[1],
+                    false,
+                    0,
+                ),
+            ];
+
+            assert_eq!(
+                process_instruction_data(&program_id, &accounts, &instruction.pack()),
+                Err(ProgramError::IncorrectProgramId),
+                "{instruction:?} with owners {owners:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_account_kind() {
+        use solana_program::pubkey::Pubkey;
+
+        let program_id = Pubkey::new_unique();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let account = |stage: VerificationStage, kind: AccountKind, size: usize| {
+            let mut data = vec![0u8; size];
+            if let [stage_byte, kind_byte, ..] = &mut data[..] {
+                (*stage_byte, *kind_byte) = (stage as u8, kind as u8);
+            }
+            data
+        };
+        let verify_work = Entrypoint::VerifyWork {
+            target: TableDecommitTarget::Original.into(),
+        };
+        let proof = account(
+            VerificationStage::Verify,
+            AccountKind::Proof,
+            PROOF_ACCOUNT_SIZE,
+        );
+        let work = account(
+            VerificationStage::Verified,
+            AccountKind::Work,
+            WORK_ACCOUNT_SIZE,
+        );
+
+        // Each account is checked for its size and kind before it's read.
+        let cases = [
+            (Entrypoint::Schedule, vec![], work.clone()),
+            (Entrypoint::Schedule, vec![0; 1], work.clone()),
+            (
+                Entrypoint::Schedule,
+                account(
+                    VerificationStage::Publish,
+                    AccountKind::Work,
+                    PROOF_ACCOUNT_SIZE,
+                ),
+                work.clone(),
+            ),
+            (Entrypoint::VerifyProof, work.clone(), work.clone()),
+            (
+                verify_work,
+                account(
+                    VerificationStage::Verify,
+                    AccountKind::Uninitialized,
+                    PROOF_ACCOUNT_SIZE,
+                ),
+                work.clone(),
+            ),
+            (verify_work, proof.clone(), vec![]),
+            (verify_work, proof.clone(), proof.clone()),
+            (
+                verify_work,
+                proof.clone(),
+                account(
+                    VerificationStage::Verify,
+                    AccountKind::Uninitialized,
+                    WORK_ACCOUNT_SIZE,
+                ),
+            ),
+            (Entrypoint::MergeWork, work.clone(), work.clone()),
+            (
+                Entrypoint::MergeWork,
+                proof.clone(),
+                account(
+                    VerificationStage::Verified,
+                    AccountKind::Uninitialized,
+                    WORK_ACCOUNT_SIZE,
+                ),
+            ),
+            (Entrypoint::MergeWork, proof.clone(), vec![0; 1]),
+        ];
+        for (instruction, mut proof_data, mut work_data) in cases {
+            let mut lamports = [0, 0];
+            let [proof_lamports, work_lamports] = &mut lamports;
+            let accounts = [
+                AccountInfo::new(
+                    &keys[0],
+                    false,
+                    true,
+                    proof_lamports,
+                    &mut proof_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    &keys[1],
+                    false,
+                    true,
+                    work_lamports,
+                    &mut work_data,
+                    &program_id,
+                    false,
+                    0,
+                ),
+            ];
+
+            assert_eq!(
+                process_instruction_data(&program_id, &accounts, &instruction.pack()),
+                Err(ProgramError::InvalidAccountData),
+                "{instruction:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_missing_account() {
+        use solana_program::pubkey::Pubkey;
+
+        assert_eq!(
+            process_instruction_data(&Pubkey::new_unique(), &[], &Entrypoint::Schedule.pack()),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_table_decommit_cache_size() {
+        use solana_program::rent::Rent;
+        use swiftness::funvec::FUNVEC_QUERIES;
+        use swiftness::stark::CacheCommitment;
+        use verify::stark_verify::table_decommit::merkle::{MerkleNode, MerkleQueue};
+
+        // Queue of `FUNVEC_QUERIES` nodes, its head, length, authentication index and verifier friendly layers,
+        // and the Merkle steps left. It used to embed a full `CacheCommitment` as well, which is read from
+        // the proof instead.
+        assert_eq!(
+            size_of::<TableDecommitCache>(),
+            FUNVEC_QUERIES * size_of::<MerkleNode>() + 32
+        );
+
+        // The account before, spelled out as it was.
+        #[allow(dead_code)]
+        #[repr(C)]
+        struct PreviousTableDecommitCache {
+            commitment: CacheCommitment,
+            merkle: MerkleQueue,
+        }
+        #[allow(dead_code)]
+        #[repr(C)]
+        struct PreviousCache {
+            legacy: LegacyCache,
+            table: PreviousTableDecommitCache,
+        }
+        #[allow(dead_code)]
+        #[repr(C)]
+        struct PreviousProofAccount {
+            proof: StarkProof,
+            cache: PreviousCache,
+            intermediate: Intermediate,
+            schedule: Schedule<RawTask, 1000>,
+        }
+
+        let size = ACCOUNT_HEADER_SIZE + size_of::<ProofAccount>();
+        let size_before = ACCOUNT_HEADER_SIZE + size_of::<PreviousProofAccount>();
+        let rent = Rent::default();
+        println!(
+            "account size: {size_before} -> {size} bytes, rent: {} -> {} lamports",
+            rent.minimum_balance(size_before),
+            rent.minimum_balance(size),
+        );
+        assert!(size < size_before);
+        assert!(rent.minimum_balance(size) < rent.minimum_balance(size_before));
+    }
+
+    #[test]
+    fn test_instruction_encoding() {
+        let golden: [(Entrypoint, &[u8]); 7] = [
+            (
+                Entrypoint::PublishFragment {
+                    offset: 256,
+                    data: &[0xab, 0xcd],
+                },
+                &[0, 0, 1, 0, 0, 0xab, 0xcd],
+            ),
+            (Entrypoint::Schedule, &[1]),
+            (Entrypoint::VerifyProof, &[2]),
+            (Entrypoint::ScheduleParallel, &[3]),
+            (Entrypoint::VerifyWork { target: [4, 2] }, &[4, 4, 2]),
+            (Entrypoint::MergeWork, &[5]),
+            (Entrypoint::ScheduleEager, &[6]),
+        ];
+
+        for (instruction, bytes) in golden {
+            assert_eq!(instruction.pack(), bytes);
+            assert_eq!(Entrypoint::unpack(bytes), Ok(instruction));
+        }
+
+        let malformed: [&[u8]; 7] = [&[], &[0, 1, 2], &[1, 0], &[4, 1], &[5, 0], &[6, 0], &[7]];
+        for bytes in malformed {
+            assert_eq!(
+                Entrypoint::unpack(bytes),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn test_idl() {
+        use core::mem::offset_of;
+        use intermediate::VerifyOutputIntermediate;
+
+        let idl = idl::idl();
+        let field = |name: &str| {
+            let field = idl["layout"]["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|field| field["name"] == name)
+                .unwrap();
+            let offset = field["offset"].as_u64().unwrap() as usize;
+            let size = field["size"].as_u64().unwrap() as usize;
+            (offset, size)
+        };
+
+        let intermediate = ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, intermediate);
+        let verify_output = intermediate + offset_of!(Intermediate, verify_output);
+        assert_eq!(field("stage"), (offset_of!(AccountHeader, stage), 1));
+        assert_eq!(field("executed"), (offset_of!(AccountHeader, executed), 2));
+        assert_eq!(field("planned"), (offset_of!(AccountHeader, planned), 2));
+        assert_eq!(
+            field("intermediate"),
+            (intermediate, size_of::<Intermediate>())
+        );
+        assert_eq!(
+            field("program_hash"),
+            (
+                verify_output + offset_of!(VerifyOutputIntermediate, program_hash),
+                size_of::<Felt>()
+            )
+        );
+        assert_eq!(
+            field("schedule").0,
+            ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, schedule)
+        );
+
+        // The `accounts` entry has a matching type, covering the whole account after the header.
+        assert_eq!(idl["accounts"][0]["name"], "ProofAccount");
+        let proof_account = idl["types"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ty| ty["name"] == "ProofAccount")
+            .unwrap();
+        let size: u64 = proof_account["type"]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["type"]["array"][1].as_u64().unwrap())
+            .sum();
+        assert_eq!(size as usize, size_of::<ProofAccount>());
+
+        // Results read with the IDL offsets are the same as the ones read with the Rust types.
+        let mut account = Box::new(ProofAccount::default());
+        let result = &mut account.intermediate.verify_output;
+        result.program_hash = Felt::from(1234);
+        result.output.push(Felt::from(5));
+        result.output.push(Felt::from(6));
+
+        let mut data = vec![0u8; ACCOUNT_HEADER_SIZE];
+        data.extend_from_slice(bytemuck::bytes_of(&*account));
+        let read_felt = |offset: usize| unsafe {
+            core::ptr::read_unaligned(data[offset..].as_ptr() as *const Felt)
+        };
+
+        let (len_offset, len_size) = field("output_len");
+        let mut len = [0u8; 8];
+        len[..len_size].copy_from_slice(&data[len_offset..len_offset + len_size]);
+        let output: Vec<Felt> = (0..u64::from_le_bytes(len) as usize)
+            .map(|i| read_felt(field("output").0 + i * size_of::<Felt>()))
+            .collect();
+
+        assert_eq!(
+            read_felt(field("program_hash").0),
+            account.intermediate.program_hash()
+        );
+        assert_eq!(output, account.intermediate.output());
+
+        // The committed IDL is only written by `cargo idl`, a missing or stale file fails here.
+        let path = idl::PATH;
+        let committed = std::fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("{path} is missing, generate it with `cargo idl`"));
+        assert!(
+            committed == idl::render(),
+            "{path} is outdated, regenerate it with `cargo idl`"
+        );
+    }
+
+    /// Verifies the proof, returns the task that panicked or returned an error.
+    fn failing_task(account_data: &mut [u8]) -> Option<Tasks> {
+        let mut stage = VerificationStage::Publish;
+        stage = process_instruction(Entrypoint::Schedule, account_data, stage).unwrap();
+
+        while stage != VerificationStage::Verified {
+            let ProofAccount { schedule, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+            let task = Tasks::try_from(&schedule.peek().unwrap()).unwrap();
+
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                process_instruction(Entrypoint::VerifyProof, account_data, stage)
+            }));
+            match result {
+                Ok(Ok(next)) => stage = next,
+                _ => return Some(task),
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn test_reject_mutated_proofs() {
+        fn bump(felt: &mut Felt) {
+            *felt = *felt + Felt::ONE;
+        }
+
+        // Mutation, and the task expected to reject it.
+        let mutations: [(&str, fn(&mut StarkProof), Tasks); 8] = [
+            (
+                "OODS value",
+                |proof| bump(&mut proof.unsent_commitment.oods_values.as_slice_mut()[0]),
+                Tasks::StarkCommit,
+            ),
+            (
+                "Merkle authentication node",
+                |proof| {
+                    let vector = &mut proof.witness.traces_witness.original.vector;
+                    bump(&mut vector.authentications.as_slice_mut()[0])
+                },
+                Tasks::TableDecommitMerkle(TableDecommitTarget::Original),
+            ),
+            // The coefficients are absorbed by the transcript, so the proof of work is no longer valid.
+            (
+                "FRI last layer coefficient",
+                |proof| {
+                    let fri = &mut proof.unsent_commitment.fri;
+                    bump(&mut fri.last_layer_coefficients.as_slice_mut()[0])
+                },
+                Tasks::StarkCommitAssign,
+            ),
+            (
+                "public memory padding value",
+                |proof| bump(&mut proof.public_input.padding_value),
+                Tasks::StarkCommit,
+            ),
+            (
+                "public memory cell",
+                |proof| bump(&mut proof.public_input.main_page.as_slice_mut()[0].value),
+                Tasks::StarkCommit,
+            ),
+            (
+                "proof of work nonce",
+                |proof| proof.unsent_commitment.proof_of_work.nonce += 1,
+                Tasks::StarkCommitAssign,
+            ),
+            // The number of bits is hashed with the nonce, fewer bits don't make the nonce valid.
+            (
+                "proof of work bits",
+                |proof| proof.config.proof_of_work.n_bits -= 1,
+                Tasks::StarkCommitAssign,
+            ),
+            // One query less is sampled, which doesn't match the number of decommitted rows.
+            (
+                "number of queries",
+                |proof| proof.config.n_queries = proof.config.n_queries - Felt::ONE,
+                Tasks::StarkVerify,
+            ),
+        ];
+
+        let proof: StarkProof = parse(include_str!("../resources/saya.json"))
+            .unwrap()
+            .transform_to();
+
+        for (name, mutate, expected) in mutations {
+            let mut account_data = vec![0u8; size_of::<ProofAccount>()];
+            let account = bytemuck::from_bytes_mut::<ProofAccount>(&mut account_data);
+            account.proof = proof.clone();
+            mutate(&mut account.proof);
+
+            let failed = failing_task(&mut account_data)
+                .unwrap_or_else(|| panic!("proof with mutated {name} was verified"));
+            assert_eq!(
+                RawTask::from(failed),
+                RawTask::from(expected),
+                "proof with mutated {name} rejected by {failed:?} instead of {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reject_other_layout() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        // `recursive.json` is a proof of the `recursive` layout, the program is built for `recursive_with_poseidon`.
+        let json = std::fs::read_to_string("resources/recursive.json").unwrap();
+
+        // Eager schedules depend on the layout, so it's checked before generating them.
+        assert_eq!(
+            process_instruction(
+                Entrypoint::ScheduleEager,
+                &mut account_image(&json),
+                VerificationStage::Publish,
+            ),
+            Err(ProgramError::Custom(19))
+        );
+
+        let account_data = &mut account_image(&json);
+        let stage = process_instruction(
+            Entrypoint::Schedule,
+            account_data,
+            VerificationStage::Publish,
+        )
+        .unwrap();
+
+        let ProofAccount { schedule, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+        let task = Tasks::try_from(&schedule.peek().unwrap()).unwrap();
+        assert!(matches!(task, Tasks::VerifyProofWithoutStark));
+
+        let payload = catch_unwind(AssertUnwindSafe(|| {
+            process_instruction(Entrypoint::VerifyProof, account_data, stage)
+        }))
+        .expect_err("proof of the recursive layout was accepted");
+        assert_eq!(
+            payload.downcast_ref::<String>().map(String::as_str),
+            Some("proof of another layout than recursive_with_poseidon")
+        );
+    }
+
+    #[test]
+    fn test_differential_monolithic() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use swiftness_air::layout::recursive_with_poseidon::Layout;
+
+        for fixture in fixtures() {
+            let json = std::fs::read_to_string(&fixture).unwrap();
+
+            let monolithic: Verdict = {
+                let mut account_data = account_image(&json);
+                let ProofAccount { proof, cache, .. } =
+                    bytemuck::from_bytes_mut::<ProofAccount>(&mut account_data);
+                let security_bits = proof.config.security_bits();
+
+                catch_unwind(AssertUnwindSafe(|| {
+                    proof.verify::<Layout>(&mut cache.legacy, security_bits)
+                }))
+                .ok()
+                .and_then(Result::ok)
+                .map(|(program_hash, output)| (program_hash, output.to_vec()))
+            };
+
+            let split = verify_image(&mut account_image(&json));
+
+            assert_eq!(
+                split,
+                monolithic,
+                "verifiers disagree on {}",
+                fixture.display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_fixtures() {
+        let bless = std::env::var_os("BLESS").is_some();
+
+        for fixture in fixtures() {
+            let json = std::fs::read_to_string(&fixture).unwrap();
+            let verdict = render_verdict(&verify_image(&mut account_image(&json)));
+
+            let expected_path = fixture.with_extension("expected");
+            if bless {
+                std::fs::write(&expected_path, &verdict).unwrap();
+            }
+            let expected = std::fs::read_to_string(&expected_path).unwrap_or_else(|_| {
+                panic!(
+                    "missing {}, run the tests with BLESS=1 to create it",
+                    expected_path.display()
+                )
+            });
+            assert_eq!(
+                verdict,
+                expected,
+                "unexpected result of {}",
+                fixture.display()
+            );
+        }
+    }
 }
@@ -0,0 +1,153 @@
+use core::fmt::{self, Write};
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::VerificationStage;
+use crate::task::{RawTask, Tasks};
+
+/// Longest message logged by the panic handler, longer ones are truncated.
+pub const PAYLOAD_LENGTH: usize = 256;
+
+/// Task being executed, kept for the panic handler.
+///
+/// SBF programs can't have writable statics, so with `custom-panic` it's kept in the heap frame, right after
+/// the position of the allocator.
+#[derive(Clone, Copy, Default, Zeroable, Pod, Debug, PartialEq)]
+#[repr(C)]
+pub struct Context {
+    pub task: RawTask,
+    pub stage: u8,
+    pub running: u8, // Zero until a task is recorded.
+    _padding: [u8; 2],
+}
+
+impl Context {
+    pub fn new(task: RawTask, stage: VerificationStage) -> Self {
+        Context {
+            task,
+            stage: stage as u8,
+            running: 1,
+            _padding: [0; 2],
+        }
+    }
+}
+
+/// Fixed size buffer for formatting without allocating, since the heap may be what ran out.
+pub struct Buffer<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Buffer<N> {
+    pub fn new() -> Self {
+        Buffer {
+            data: [0; N],
+            len: 0,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+impl<const N: usize> Default for Buffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Write for Buffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // Truncated silently, the start of the message is the most useful part.
+        let n = s.len().min(N - self.len);
+        self.data[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+/// What the panic handler logs with `sol_log_data`.
+pub struct Report {
+    pub task: RawTask,
+    pub name: Buffer<48>,
+    pub stage: u8,
+    pub payload: Buffer<PAYLOAD_LENGTH>,
+}
+
+impl Report {
+    pub fn new(context: &Context, message: &dyn fmt::Display) -> Self {
+        let mut report = Report {
+            task: context.task,
+            name: Buffer::new(),
+            stage: context.stage,
+            payload: Buffer::new(),
+        };
+        let _ = match (context.running, Tasks::try_from(&context.task)) {
+            (0, _) => write!(report.name, "-"),
+            (_, Ok(task)) => write!(report.name, "{task:?}"),
+            (_, Err(_)) => write!(report.name, "invalid"),
+        };
+        let _ = write!(report.payload, "{message}");
+        report
+    }
+
+    /// Raw task, its name, the stage and the message, one field each.
+    pub fn fields(&self) -> [&[u8]; 4] {
+        [
+            &self.task,
+            self.name.as_bytes(),
+            core::slice::from_ref(&self.stage),
+            self.payload.as_bytes(),
+        ]
+    }
+}
+
+#[cfg(all(target_os = "solana", feature = "custom-panic"))]
+fn context() -> *mut Context {
+    (solana_program::entrypoint::HEAP_START_ADDRESS as usize + size_of::<usize>()) as *mut Context
+}
+
+/// Records the task about to be executed.
+#[cfg(all(target_os = "solana", feature = "custom-panic"))]
+pub fn set_context(context: Context) {
+    // SAFETY: reserved by the allocator of `custom-heap`, which `custom-panic` enables.
+    unsafe { *self::context() = context };
+}
+
+#[cfg(all(target_os = "solana", feature = "custom-panic"))]
+#[unsafe(no_mangle)]
+fn custom_panic(info: &core::panic::PanicInfo<'_>) {
+    // SAFETY: zeroed with the heap frame, or written by `set_context`.
+    let report = Report::new(unsafe { &*context() }, info);
+    solana_program::log::sol_log_data(&report.fields());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_report() {
+        let task = RawTask::from(Tasks::StarkVerifyLayerDecommitmentMont(7));
+        let context = Context::new(task, VerificationStage::Verify);
+        let report = Report::new(&context, &"called `Option::unwrap()` on a `None` value");
+        let fields: [&[u8]; 4] = [
+            &task,
+            b"StarkVerifyLayerDecommitmentMont(7)",
+            &[1],
+            b"called `Option::unwrap()` on a `None` value",
+        ];
+        assert_eq!(report.fields(), fields);
+
+        // Long messages are truncated rather than allocated.
+        let report = Report::new(&context, &"x".repeat(2 * PAYLOAD_LENGTH));
+        assert_eq!(report.payload.as_bytes(), &[b'x'; PAYLOAD_LENGTH]);
+
+        // Before any task is recorded, and with an undecodable one.
+        let report = Report::new(&Context::default(), &"");
+        assert_eq!(report.name.as_bytes(), b"-");
+        let report = Report::new(&Context::new([255; 4], VerificationStage::Verify), &"");
+        assert_eq!(report.name.as_bytes(), b"invalid");
+    }
+}
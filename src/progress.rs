@@ -0,0 +1,63 @@
+use core::mem::offset_of;
+
+use solana_program::program_error::ProgramError;
+
+use crate::schedule::Schedule;
+use crate::task::{RawTask, Tasks};
+use crate::{ACCOUNT_HEADER_SIZE, AccountHeader, ProofAccount, VerificationStage};
+
+/// Progress of the verification, as seen in a snapshot of the proof account.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    pub stage: VerificationStage,
+    pub executed: usize,
+    pub remaining: usize,
+    pub current: Option<Tasks>, // Task executed by the next `VerifyProof`.
+}
+
+impl Progress {
+    /// Executed share of the tasks known so far, as tasks are split it can move backwards,
+    /// unless the schedule was eager.
+    pub fn percentage(&self) -> f64 {
+        match self.stage {
+            VerificationStage::Publish => 0.0,
+            VerificationStage::Verify => {
+                100.0 * self.executed as f64 / (self.executed + self.remaining) as f64
+            }
+            VerificationStage::Verified => 100.0,
+        }
+    }
+}
+
+/// Parses the progress from the account data, including the header. The data doesn't have to be aligned.
+pub fn progress(account_data: &[u8]) -> Result<Progress, ProgramError> {
+    if account_data.len() < ACCOUNT_HEADER_SIZE + size_of::<ProofAccount>() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+
+    let header: AccountHeader = bytemuck::pod_read_unaligned(&account_data[..ACCOUNT_HEADER_SIZE]);
+    let stage = VerificationStage::try_from(header.stage)?;
+
+    let offset = ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, schedule);
+    let schedule: Schedule<RawTask, 1000> = bytemuck::pod_read_unaligned(
+        &account_data[offset..offset + size_of::<Schedule<RawTask, 1000>>()],
+    );
+
+    let (remaining, current) = match stage {
+        VerificationStage::Verify => (
+            schedule.remaining(),
+            schedule
+                .peek()
+                .map(|task| Tasks::try_from(&task))
+                .transpose()?,
+        ),
+        _ => (0, None),
+    };
+
+    Ok(Progress {
+        stage,
+        executed: header.executed as usize,
+        remaining,
+        current,
+    })
+}
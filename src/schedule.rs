@@ -1,6 +1,6 @@
-use std::collections::VecDeque;
-
 use bytemuck::{Pod, Zeroable};
+use solana_program::program_error::ProgramError;
+use swiftness::funvec;
 use swiftness::types::StarkProof;
 
 use crate::{Cache, intermediate::Intermediate, task::Tasks};
@@ -14,6 +14,7 @@ where
 {
     data: [T; N],
     top: usize,
+    eager: usize, // Non-zero if the tasks were generated upfront, so executing them adds none.
 }
 
 unsafe impl<T: Pod + Zeroable + Default, const N: usize> Pod for Schedule<T, N> {}
@@ -27,6 +28,7 @@ where
         Self {
             data: [T::default(); N],
             top: 0,
+            eager: 0,
         }
     }
 }
@@ -35,26 +37,39 @@ impl<T, const N: usize> Schedule<T, N>
 where
     T: Pod + Zeroable + Default + From<Tasks>,
 {
+    /// Pushes every task of the verification, generated upfront from the proof parameters, so
+    /// the number of tasks is known before any of them is executed.
     pub fn generate_tasks(
         &mut self,
-        proof: &mut StarkProof,
-        cache: &mut Cache,
-        intermediate: &mut Intermediate,
-    ) {
-        let mut queue = VecDeque::new();
-        queue.push_back(Tasks::VerifyProofWithoutStark);
-
-        while let Some(task) = queue.pop_front() {
-            // Add the current task to schedule
-            self.push(task.into());
-            let task_view = task.view(proof, cache, intermediate);
-            let children = task_view.children();
-
-            // Add the children in a stack-like manner
-            for child in children.into_iter().rev() {
-                queue.push_front(child);
+        proof: &StarkProof,
+        parallel: bool,
+    ) -> Result<(), ProgramError> {
+        // Tasks are repeated for every query, so a modified proof could describe far too many.
+        if funvec::cast_felt(&proof.config.n_queries) > N as u64 {
+            return Err(ProgramError::Custom(19));
+        }
+
+        // Depth-first, in the order of execution.
+        let mut tasks = Vec::new();
+        let mut stack = vec![Tasks::VerifyProofWithoutStark];
+        while let Some(task) = stack.pop() {
+            if self.top + tasks.len() == N {
+                return Err(ProgramError::Custom(19));
             }
+            tasks.push(T::from(task));
+            let children = stack.len();
+            task.children(proof, parallel, |child| stack.push(child));
+            stack[children..].reverse();
         }
+        tasks.reverse();
+        self.push_slice(&tasks);
+        self.eager = 1;
+
+        Ok(())
+    }
+
+    pub fn is_eager(&self) -> bool {
+        self.eager != 0
     }
 
     pub fn finished(&self) -> bool {
@@ -70,11 +85,29 @@ where
         }
     }
 
+    /// The task that will be executed next, without removing it.
+    pub fn peek(&self) -> Option<T> {
+        self.top.checked_sub(1).map(|top| self.data[top])
+    }
+
     pub fn push(&mut self, value: T) {
         self.data[self.top] = value;
         self.top += 1;
     }
 
+    /// Pushes the children of the executed task, so they're executed in order, without allocating.
+    pub fn push_children(
+        &mut self,
+        task: Tasks,
+        proof: &StarkProof,
+        cache: &Cache,
+        intermediate: &Intermediate,
+    ) {
+        let start = self.top;
+        task.next_children(proof, cache, intermediate, |child| self.push(child.into()));
+        self.data[start..self.top].reverse();
+    }
+
     /// Tasks should be in the reverse order of execution.
     pub fn push_slice(&mut self, vec: &[T]) {
         self.data[self.top..self.top + vec.len()].copy_from_slice(vec);
@@ -83,6 +116,7 @@ where
 
     pub fn flush(&mut self) {
         self.top = 0;
+        self.eager = 0;
     }
 
     pub fn from_slice(vec: &[T]) -> Self {
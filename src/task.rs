@@ -1,6 +1,11 @@
+use core::iter;
+
 use solana_program::program_error::ProgramError;
+use swiftness::funvec;
 pub use swiftness_stark::types::{Felt, StarkProof};
 
+pub use crate::verify::stark_verify::table_decommit::TableDecommitTarget;
+
 use crate::Cache;
 use crate::verify::generate_queries::GenerateQueriesTask;
 use crate::verify::stark_commit::{
@@ -15,7 +20,10 @@ use crate::verify::stark_verify::fri_verify::fri_verify_layers::compute_next_lay
 use crate::verify::stark_verify::fri_verify::fri_verify_layers::decommitment_mont::StarkVerifyLayerDecommitmentMontTask;
 use crate::verify::stark_verify::fri_verify::fri_verify_layers::layer::StarkVerifyLayerTask;
 use crate::verify::stark_verify::fri_verify::last_layer::StarkVerifyLastLayerTask;
-use crate::verify::stark_verify::table_decommit::{TableDecommitTarget, TableDecommitTask};
+use crate::verify::stark_verify::oods_point::StarkVerifyOodsPointTask;
+use crate::verify::stark_verify::table_decommit::TableDecommitTask;
+use crate::verify::stark_verify::table_decommit::merkle::TableDecommitMerkleTask;
+use crate::verify::stark_verify::table_decommit::parallel::TableDecommitJoinTask;
 use crate::verify::verify_output::VerifyOutputTask;
 use crate::{intermediate::Intermediate, verify::VerifyProofTask};
 
@@ -40,66 +48,260 @@ pub enum Tasks {
     StarkVerifyLayerDecommitmentMont(usize) = 15,
     ComputeNextLayer(usize) = 16,
     ComputeNextInner(usize) = 17,
+    StarkVerifyOodsPoint(usize) = 18,
+    TableDecommitMerkle(TableDecommitTarget) = 19,
+    TableDecommitJoin = 20,
 }
 
 pub type RawTask = [u8; 4];
 
 pub trait Task {
-    fn execute(&mut self) -> Vec<Tasks>;
-    fn children(&self) -> Vec<Tasks>;
+    fn execute(&mut self);
 }
 
 impl Tasks {
+    /// Passes the tasks to execute after this one to `push`, in order, once it was executed.
+    ///
+    /// Tasks whose work depends on the sampled queries are scheduled exactly as many times as
+    /// the state left by this one needs.
+    pub fn next_children(
+        self,
+        proof: &StarkProof,
+        cache: &Cache,
+        intermediate: &Intermediate,
+        mut push: impl FnMut(Tasks),
+    ) {
+        let parallel = cache.parallel.is_enabled();
+
+        match self {
+            Tasks::StarkVerify => {
+                Self::stark_verify_children(intermediate.verify.queries.len()).for_each(push)
+            }
+            // One inner task per coset, until no query is left.
+            Tasks::ComputeNextLayer(i) | Tasks::ComputeNextInner(i) => {
+                if !cache.legacy.stark.fri.fri_queries.is_empty() {
+                    push(Tasks::ComputeNextInner(i));
+                }
+            }
+            Tasks::TableDecommit(target) if parallel && target.slot().is_some() => {}
+            Tasks::TableDecommit(target) => push(Tasks::TableDecommitMerkle(target)),
+            Tasks::TableDecommitMerkle(target) => {
+                if !cache.table.merkle.reached_root() {
+                    push(Tasks::TableDecommitMerkle(target));
+                }
+            }
+            task => task.children(proof, parallel, push),
+        }
+    }
+
+    /// Passes the tasks to execute after this one to `push`, in order.
+    ///
+    /// Only depends on the parameters of the proof, so the whole schedule can be generated
+    /// upfront. Tasks whose work depends on the sampled queries are scheduled as many times as
+    /// it could take, and skip what's already done.
+    pub fn children(self, proof: &StarkProof, parallel: bool, push: impl FnMut(Tasks)) {
+        let n_queries = funvec::cast_felt(&proof.config.n_queries) as usize;
+
+        match self {
+            Tasks::VerifyProofWithoutStark => [
+                Tasks::StarkCommit,
+                Tasks::GenerateQueries,
+                Tasks::StarkVerify,
+                Tasks::VerifyOutput,
+            ]
+            .into_iter()
+            .for_each(push),
+            Tasks::StarkCommit => [
+                Tasks::StarkCommitOodsCoef,
+                Tasks::StarkCommitFri,
+                Tasks::StarkCommitAssign,
+            ]
+            .into_iter()
+            .for_each(push),
+            Tasks::StarkVerify => Self::stark_verify_children(n_queries).for_each(push),
+            Tasks::StarkVerifyFri => [
+                Tasks::StarkVerifyLayersTask,
+                Tasks::StarkVerifyLastLayerTask,
+            ]
+            .into_iter()
+            .for_each(push),
+            Tasks::StarkVerifyLayersTask => {
+                let n_layers = funvec::cast_felt(&proof.config.fri.n_layers) as usize;
+                (0..n_layers.saturating_sub(1))
+                    .map(Tasks::StarkVerifyFriLayer)
+                    .for_each(push)
+            }
+            Tasks::StarkVerifyFriLayer(i) => [
+                Tasks::ComputeNextLayer(i),
+                Tasks::StarkVerifyLayerDecommitmentMont(i),
+                Tasks::TableDecommit(TableDecommitTarget::Fri(i as u8)),
+                Tasks::StarkVerifyLayerAssignNext,
+            ]
+            .into_iter()
+            .for_each(push),
+            // Every inner task processes a coset of at least one query.
+            Tasks::ComputeNextLayer(i) => {
+                iter::repeat_n(Tasks::ComputeNextInner(i), n_queries).for_each(push)
+            }
+            // The Merkle tree is verified in a work account instead.
+            Tasks::TableDecommit(target) if parallel && target.slot().is_some() => {}
+            Tasks::TableDecommit(target) => iter::repeat_n(
+                Tasks::TableDecommitMerkle(target),
+                target.merkle_steps(proof) as usize,
+            )
+            .for_each(push),
+            Tasks::VerifyOutput
+            | Tasks::StarkCommitOodsCoef
+            | Tasks::StarkCommitFri
+            | Tasks::StarkCommitAssign
+            | Tasks::GenerateQueries
+            | Tasks::StarkVerifyLastLayerTask
+            | Tasks::StarkVerifyLayerAssignNext
+            | Tasks::StarkVerifyLayerDecommitmentMont(_)
+            | Tasks::ComputeNextInner(_)
+            | Tasks::StarkVerifyOodsPoint(_)
+            | Tasks::TableDecommitMerkle(_)
+            | Tasks::TableDecommitJoin => {}
+        }
+    }
+
+    fn stark_verify_children(n_queries: usize) -> impl Iterator<Item = Tasks> {
+        (0..n_queries).map(Tasks::StarkVerifyOodsPoint).chain([
+            Tasks::TableDecommit(TableDecommitTarget::Original),
+            Tasks::TableDecommit(TableDecommitTarget::Interaction),
+            Tasks::TableDecommit(TableDecommitTarget::Composition),
+            Tasks::StarkVerifyFri,
+        ])
+    }
+
+    /// Views the task over the account, failing if its parameters don't match the proof.
     pub fn view<'a>(
         self,
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Box<dyn Task + 'a> {
-        match self {
+    ) -> Result<TaskView<'a>, ProgramError> {
+        Ok(match self {
             Tasks::VerifyProofWithoutStark => {
-                Box::new(VerifyProofTask::view(proof, cache, intermediate))
+                TaskView::VerifyProofWithoutStark(VerifyProofTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkVerify => Box::new(StarkVerifyTask::view(proof, cache, intermediate)),
-            Tasks::VerifyOutput => Box::new(VerifyOutputTask::view(proof, cache, intermediate)),
-            Tasks::TableDecommit(target) => {
-                Box::new(TableDecommitTask::view(target, proof, cache, intermediate))
+            Tasks::StarkVerify => {
+                TaskView::StarkVerify(StarkVerifyTask::view(proof, cache, intermediate))
+            }
+            Tasks::VerifyOutput => {
+                TaskView::VerifyOutput(VerifyOutputTask::view(proof, cache, intermediate))
+            }
+            Tasks::TableDecommit(target) => TaskView::TableDecommit(TableDecommitTask::view(
+                target,
+                proof,
+                cache,
+                intermediate,
+            )?),
+            Tasks::StarkCommit => {
+                TaskView::StarkCommit(StarkCommitTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkCommit => Box::new(StarkCommitTask::view(proof, cache, intermediate)),
             Tasks::GenerateQueries => {
-                Box::new(GenerateQueriesTask::view(proof, cache, intermediate))
+                TaskView::GenerateQueries(GenerateQueriesTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkCommitOodsCoef => {
-                Box::new(StarkCommitOodsCoefTask::view(proof, cache, intermediate))
+            Tasks::StarkCommitOodsCoef => TaskView::StarkCommitOodsCoef(
+                StarkCommitOodsCoefTask::view(proof, cache, intermediate),
+            ),
+            Tasks::StarkCommitFri => {
+                TaskView::StarkCommitFri(StarkCommitFriTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkCommitFri => Box::new(StarkCommitFriTask::view(proof, cache, intermediate)),
             Tasks::StarkCommitAssign => {
-                Box::new(StarkCommitAssignTask::view(proof, cache, intermediate))
+                TaskView::StarkCommitAssign(StarkCommitAssignTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkVerifyFri => Box::new(StarkVerifyFriTask::view(proof, cache, intermediate)),
-            Tasks::StarkVerifyLayersTask => {
-                Box::new(StarkVerifyLayersTask::view(proof, cache, intermediate))
+            Tasks::StarkVerifyFri => {
+                TaskView::StarkVerifyFri(StarkVerifyFriTask::view(proof, cache, intermediate))
             }
-            Tasks::StarkVerifyLastLayerTask => {
-                Box::new(StarkVerifyLastLayerTask::view(proof, cache, intermediate))
+            Tasks::StarkVerifyLayersTask => TaskView::StarkVerifyLayersTask(
+                StarkVerifyLayersTask::view(proof, cache, intermediate),
+            ),
+            Tasks::StarkVerifyLastLayerTask => TaskView::StarkVerifyLastLayerTask(
+                StarkVerifyLastLayerTask::view(proof, cache, intermediate),
+            ),
+            Tasks::StarkVerifyFriLayer(i) => TaskView::StarkVerifyFriLayer(
+                StarkVerifyLayerTask::view(i, proof, cache, intermediate)?,
+            ),
+            Tasks::StarkVerifyLayerAssignNext => TaskView::StarkVerifyLayerAssignNext(
+                StarkVerifyLayerAssignNextTask::view(proof, cache, intermediate),
+            ),
+            Tasks::StarkVerifyLayerDecommitmentMont(i) => {
+                TaskView::StarkVerifyLayerDecommitmentMont(
+                    StarkVerifyLayerDecommitmentMontTask::view(i, proof, cache, intermediate)?,
+                )
             }
-            Tasks::StarkVerifyFriLayer(i) => {
-                Box::new(StarkVerifyLayerTask::view(i, proof, cache, intermediate))
+            Tasks::ComputeNextLayer(i) => {
+                TaskView::ComputeNextLayer(ComputeNextTask::view(i, proof, cache, intermediate)?)
             }
-            Tasks::StarkVerifyLayerAssignNext => Box::new(StarkVerifyLayerAssignNextTask::view(
+            Tasks::ComputeNextInner(i) => TaskView::ComputeNextInner(ComputeNextInnerTask::view(
+                i,
                 proof,
                 cache,
                 intermediate,
-            )),
-            Tasks::StarkVerifyLayerDecommitmentMont(i) => Box::new(
-                StarkVerifyLayerDecommitmentMontTask::view(i, proof, cache, intermediate),
+            )?),
+            Tasks::StarkVerifyOodsPoint(i) => TaskView::StarkVerifyOodsPoint(
+                StarkVerifyOodsPointTask::view(i, proof, cache, intermediate),
             ),
-            Tasks::ComputeNextLayer(i) => {
-                Box::new(ComputeNextTask::view(i, proof, cache, intermediate))
-            }
-            Tasks::ComputeNextInner(i) => {
-                Box::new(ComputeNextInnerTask::view(i, proof, cache, intermediate))
+            Tasks::TableDecommitMerkle(target) => TaskView::TableDecommitMerkle(
+                TableDecommitMerkleTask::view(target, proof, cache, intermediate)?,
+            ),
+            Tasks::TableDecommitJoin => {
+                TaskView::TableDecommitJoin(TableDecommitJoinTask::view(proof, cache, intermediate))
             }
+        })
+    }
+}
+
+/// A task viewed over the account, dispatched with a `match` rather than boxed, so executing it
+/// doesn't allocate.
+pub enum TaskView<'a> {
+    VerifyProofWithoutStark(VerifyProofTask<'a>),
+    StarkVerify(StarkVerifyTask<'a>),
+    VerifyOutput(VerifyOutputTask<'a>),
+    TableDecommit(TableDecommitTask<'a>),
+    StarkCommit(StarkCommitTask<'a>),
+    StarkCommitOodsCoef(StarkCommitOodsCoefTask<'a>),
+    StarkCommitFri(StarkCommitFriTask<'a>),
+    StarkCommitAssign(StarkCommitAssignTask<'a>),
+    GenerateQueries(GenerateQueriesTask<'a>),
+    StarkVerifyFri(StarkVerifyFriTask<'a>),
+    StarkVerifyLayersTask(StarkVerifyLayersTask<'a>),
+    StarkVerifyLastLayerTask(StarkVerifyLastLayerTask<'a>),
+    StarkVerifyFriLayer(StarkVerifyLayerTask<'a>),
+    StarkVerifyLayerAssignNext(StarkVerifyLayerAssignNextTask<'a>),
+    StarkVerifyLayerDecommitmentMont(StarkVerifyLayerDecommitmentMontTask<'a>),
+    ComputeNextLayer(ComputeNextTask<'a>),
+    ComputeNextInner(ComputeNextInnerTask<'a>),
+    StarkVerifyOodsPoint(StarkVerifyOodsPointTask<'a>),
+    TableDecommitMerkle(TableDecommitMerkleTask<'a>),
+    TableDecommitJoin(TableDecommitJoinTask<'a>),
+}
+
+impl Task for TaskView<'_> {
+    fn execute(&mut self) {
+        match self {
+            TaskView::VerifyProofWithoutStark(task) => task.execute(),
+            TaskView::StarkVerify(task) => task.execute(),
+            TaskView::VerifyOutput(task) => task.execute(),
+            TaskView::TableDecommit(task) => task.execute(),
+            TaskView::StarkCommit(task) => task.execute(),
+            TaskView::StarkCommitOodsCoef(task) => task.execute(),
+            TaskView::StarkCommitFri(task) => task.execute(),
+            TaskView::StarkCommitAssign(task) => task.execute(),
+            TaskView::GenerateQueries(task) => task.execute(),
+            TaskView::StarkVerifyFri(task) => task.execute(),
+            TaskView::StarkVerifyLayersTask(task) => task.execute(),
+            TaskView::StarkVerifyLastLayerTask(task) => task.execute(),
+            TaskView::StarkVerifyFriLayer(task) => task.execute(),
+            TaskView::StarkVerifyLayerAssignNext(task) => task.execute(),
+            TaskView::StarkVerifyLayerDecommitmentMont(task) => task.execute(),
+            TaskView::ComputeNextLayer(task) => task.execute(),
+            TaskView::ComputeNextInner(task) => task.execute(),
+            TaskView::StarkVerifyOodsPoint(task) => task.execute(),
+            TaskView::TableDecommitMerkle(task) => task.execute(),
+            TaskView::TableDecommitJoin(task) => task.execute(),
         }
     }
 }
@@ -107,10 +309,11 @@ impl Tasks {
 impl TryFrom<&RawTask> for Tasks {
     type Error = ProgramError;
 
+    /// Only accepts the encoding produced by `RawTask::from`, unused bytes have to be zero.
     fn try_from(value: &RawTask) -> Result<Self, Self::Error> {
         let [variant, tail @ ..] = value;
 
-        Ok(match variant {
+        let task = match variant {
             1 => Tasks::VerifyProofWithoutStark,
             2 => Tasks::StarkVerify,
             3 => Tasks::VerifyOutput,
@@ -128,8 +331,16 @@ impl TryFrom<&RawTask> for Tasks {
             15 => Tasks::StarkVerifyLayerDecommitmentMont(tail[0] as usize),
             16 => Tasks::ComputeNextLayer(tail[0] as usize),
             17 => Tasks::ComputeNextInner(tail[0] as usize),
+            18 => Tasks::StarkVerifyOodsPoint(u16::from_le_bytes([tail[0], tail[1]]) as usize),
+            19 => Tasks::TableDecommitMerkle(TableDecommitTarget::try_from([tail[0], tail[1]])?),
+            20 => Tasks::TableDecommitJoin,
             _ => return Err(ProgramError::Custom(2)),
-        })
+        };
+
+        if RawTask::from(task) != *value {
+            return Err(ProgramError::Custom(2));
+        }
+        Ok(task)
     }
 }
 
@@ -139,13 +350,10 @@ impl From<Tasks> for RawTask {
             Tasks::VerifyProofWithoutStark => [1, 0, 0, 0],
             Tasks::StarkVerify => [2, 0, 0, 0],
             Tasks::VerifyOutput => [3, 0, 0, 0],
-            Tasks::TableDecommit(target) => match target {
-                TableDecommitTarget::Invalid => [4, 0, 0, 0],
-                TableDecommitTarget::Original => [4, 1, 0, 0],
-                TableDecommitTarget::Interaction => [4, 2, 0, 0],
-                TableDecommitTarget::Composition => [4, 3, 0, 0],
-                TableDecommitTarget::Fri(i) => [4, 4, i as u8, 0],
-            },
+            Tasks::TableDecommit(target) => {
+                let [variant, fri] = target.into();
+                [4, variant, fri, 0]
+            }
             Tasks::StarkCommit => [5, 0, 0, 0],
             Tasks::StarkCommitOodsCoef => [6, 0, 0, 0],
             Tasks::StarkCommitFri => [7, 0, 0, 0],
@@ -159,6 +367,16 @@ impl From<Tasks> for RawTask {
             Tasks::StarkVerifyLayerDecommitmentMont(i) => [15, i as u8, 0, 0],
             Tasks::ComputeNextLayer(i) => [16, i as u8, 0, 0],
             Tasks::ComputeNextInner(i) => [17, i as u8, 0, 0],
+            // Queries are limited by the capacity of a `FunVec`, more than fit in a byte.
+            Tasks::StarkVerifyOodsPoint(i) => {
+                let [low, high] = (i as u16).to_le_bytes();
+                [18, low, high, 0]
+            }
+            Tasks::TableDecommitMerkle(target) => {
+                let [variant, fri] = target.into();
+                [19, variant, fri, 0]
+            }
+            Tasks::TableDecommitJoin => [20, 0, 0, 0],
         }
     }
 }
@@ -1,4 +1,5 @@
 use swiftness::stark::Error;
+use swiftness::types::Felt;
 use swiftness_air::{
     Transcript,
     domains::StarkDomains,
@@ -9,7 +10,7 @@ pub use swiftness_stark::types::StarkProof;
 use crate::{
     Cache,
     intermediate::{Intermediate, VerifyIntermediate},
-    task::{Task, Tasks},
+    task::Task,
 };
 
 pub mod generate_queries;
@@ -23,11 +24,34 @@ pub struct VerifyProofTask<'a> {
     intermediate: &'a mut VerifyIntermediate,
 }
 
+/// Name of the layout the program is built for, as stored in the public input.
+pub const LAYOUT: &str = "recursive_with_poseidon";
+
+/// Whether the proof is of `LAYOUT`, the layout is a Cairo short string, its bytes in big-endian.
+pub fn is_supported_layout(proof: &StarkProof) -> bool {
+    proof.public_input.layout == Felt::from_bytes_be_slice(LAYOUT.as_bytes())
+}
+
+/// Validates the proof parameters, returns the number of original and interaction columns.
+pub fn validate_config(proof: &StarkProof) -> Result<(u32, u32), Error> {
+    let security_bits = proof.config.security_bits();
+    let n_original_columns =
+        Layout::get_num_columns_first(&proof.public_input).ok_or(Error::ColumnMissing)?;
+    let n_interaction_columns =
+        Layout::get_num_columns_second(&proof.public_input).ok_or(Error::ColumnMissing)?;
+
+    proof.config.validate(
+        security_bits,
+        n_original_columns.into(),
+        n_interaction_columns.into(),
+    )?;
+
+    Ok((n_original_columns, n_interaction_columns))
+}
+
 impl Task for VerifyProofTask<'_> {
     // let _res = self.proof.verify::<Layout>(self.cache, security_bits);
-    fn execute(&mut self) -> Vec<Tasks> {
-        let security_bits = self.proof.config.security_bits();
-
+    fn execute(&mut self) {
         let VerifyIntermediate {
             n_original_columns,
             n_interaction_columns,
@@ -36,22 +60,13 @@ impl Task for VerifyProofTask<'_> {
             ..
         } = self.intermediate;
 
-        *n_original_columns = Layout::get_num_columns_first(&self.proof.public_input)
-            .ok_or(Error::ColumnMissing)
-            .unwrap();
-
-        *n_interaction_columns = Layout::get_num_columns_second(&self.proof.public_input)
-            .ok_or(Error::ColumnMissing)
-            .unwrap();
+        // Proofs of other layouts would fail somewhere down the line, reject them upfront.
+        assert!(
+            is_supported_layout(self.proof),
+            "proof of another layout than {LAYOUT}"
+        );
 
-        self.proof
-            .config
-            .validate(
-                security_bits,
-                (*n_original_columns).into(),
-                (*n_interaction_columns).into(),
-            )
-            .unwrap();
+        (*n_original_columns, *n_interaction_columns) = validate_config(self.proof).unwrap();
 
         // Validate the public input.
         *stark_domains = StarkDomains::new(
@@ -68,17 +83,6 @@ impl Task for VerifyProofTask<'_> {
                 .public_input
                 .get_hash(self.proof.config.n_verifier_friendly_commitment_layers),
         );
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![
-            Tasks::StarkCommit,
-            Tasks::GenerateQueries,
-            Tasks::StarkVerify,
-            Tasks::VerifyOutput,
-        ]
     }
 }
 
@@ -6,7 +6,6 @@ use swiftness_air::layout::recursive_with_poseidon::Layout;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkCommitTask;
 
@@ -16,7 +15,7 @@ pub struct StarkCommitAssignTask<'a> {
 
 impl Task for StarkCommitAssignTask<'_> {
     // stark_commit() - last part
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let StarkCommitTask {
             result,
             cache,
@@ -53,12 +52,6 @@ impl Task for StarkCommitAssignTask<'_> {
 
         oods_values.overwrite(unsent_commitment.oods_values.as_slice());
         interaction_after_oods.overwrite(oods_coefficients);
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
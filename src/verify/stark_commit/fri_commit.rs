@@ -4,7 +4,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkCommitTask;
 
@@ -14,7 +13,7 @@ pub struct StarkCommitFriTask<'a> {
 
 impl Task for StarkCommitFriTask<'_> {
     // stark_commit() - last part
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let StarkCommitTask {
             intermediate,
             transcript,
@@ -28,12 +27,6 @@ impl Task for StarkCommitFriTask<'_> {
             &unsent_commitment.fri,
             &config.fri,
         );
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
@@ -18,7 +18,6 @@ use swiftness_air::trace::Commitment;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 mod assign;
 mod fri_commit;
@@ -52,7 +51,7 @@ pub struct StarkCommitIntermediate {
 
 impl Task for StarkCommitTask<'_> {
     // stark_commit()
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let StarkCommitTask {
             cache,
             transcript,
@@ -94,7 +93,7 @@ impl Task for StarkCommitTask<'_> {
         intermediate.interaction_after_composition = transcript.random_felt_to_prover();
 
         // Read OODS values.
-        transcript.read_felt_vector_from_prover(&unsent_commitment.oods_values.to_vec());
+        transcript.read_felt_vector_from_prover(unsent_commitment.oods_values.as_slice());
 
         // // Check that the trace and the composition agree at oods_point.
         verify_oods::<Layout>(
@@ -108,16 +107,6 @@ impl Task for StarkCommitTask<'_> {
             &stark_domains.trace_generator,
         )
         .unwrap();
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![
-            Tasks::StarkCommitOodsCoef,
-            Tasks::StarkCommitFri,
-            Tasks::StarkCommitAssign,
-        ]
     }
 }
 
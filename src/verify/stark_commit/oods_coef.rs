@@ -7,7 +7,6 @@ use swiftness_air::layout::recursive_with_poseidon::Layout;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkCommitTask;
 
@@ -17,7 +16,7 @@ pub struct StarkCommitOodsCoefTask<'a> {
 
 impl Task for StarkCommitOodsCoefTask<'_> {
     // stark_commit() - last part
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let StarkCommitTask {
             intermediate,
             transcript,
@@ -35,12 +34,6 @@ impl Task for StarkCommitOodsCoefTask<'_> {
             intermediate.oods_alpha,
             n as u32,
         );
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
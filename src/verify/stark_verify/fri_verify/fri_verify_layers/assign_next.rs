@@ -4,7 +4,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 pub struct StarkVerifyLayerAssignNextTask<'a> {
     cache: &'a mut FriVerifyCache,
@@ -12,21 +11,16 @@ pub struct StarkVerifyLayerAssignNextTask<'a> {
 
 impl Task for StarkVerifyLayerAssignNextTask<'_> {
     // fri_verify_layers(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let FriVerifyCache {
             fri_queries,
             next_layer_cache,
             ..
         } = self.cache;
 
+        assert!(fri_queries.is_empty(), "FRI queries left in the layer");
         fri_queries.flush();
         fri_queries.extend(next_layer_cache.next_queries.as_slice());
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
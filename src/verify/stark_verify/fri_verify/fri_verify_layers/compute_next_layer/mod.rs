@@ -1,3 +1,4 @@
+use solana_program::program_error::ProgramError;
 use swiftness::swiftness_fri::ComputeNextLayerCache;
 use swiftness::swiftness_fri::FriVerifyCache;
 
@@ -6,7 +7,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::layer::StarkVerifyLayerTask;
 
@@ -18,15 +18,13 @@ pub struct ComputeNextTask<'a> {
 
 impl Task for ComputeNextTask<'_> {
     // compute_next_layer(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         // Original
 
         let StarkVerifyLayerTask { cache, .. } = &mut self.parent;
 
         let FriVerifyCache {
-            fri_queries: queries,
-            next_layer_cache,
-            ..
+            next_layer_cache, ..
         } = cache;
 
         let ComputeNextLayerCache {
@@ -39,16 +37,6 @@ impl Task for ComputeNextTask<'_> {
         next_queries.flush();
         verify_indices.flush();
         verify_y_values.flush();
-
-        if queries.is_empty() {
-            return vec![];
-        }
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![Tasks::ComputeNextInner(self.parent.layer_index)]
     }
 }
 
@@ -58,9 +46,9 @@ impl<'a> ComputeNextTask<'a> {
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Self {
-        ComputeNextTask {
-            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate),
-        }
+    ) -> Result<Self, ProgramError> {
+        Ok(ComputeNextTask {
+            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate)?,
+        })
     }
 }
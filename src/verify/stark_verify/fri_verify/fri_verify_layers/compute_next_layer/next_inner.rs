@@ -1,3 +1,4 @@
+use solana_program::program_error::ProgramError;
 use swiftness::funvec;
 use swiftness::swiftness_fri::ComputeNextLayerCache;
 use swiftness::swiftness_fri::FriVerifyCache;
@@ -10,7 +11,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 use crate::verify::stark_verify::fri_verify::fri_verify_layers::layer::StarkVerifyLayerContext;
 use crate::verify::stark_verify::fri_verify::fri_verify_layers::layer::StarkVerifyLayerTask;
 
@@ -20,15 +20,10 @@ pub struct ComputeNextInnerTask<'a> {
 
 impl Task for ComputeNextInnerTask<'_> {
     // compute_next_layer(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         // Original
 
-        let StarkVerifyLayerTask {
-            cache,
-            context,
-            layer_index,
-            ..
-        } = &mut self.parent;
+        let StarkVerifyLayerTask { cache, context, .. } = &mut self.parent;
 
         let FriVerifyCache {
             fri_queries: queries,
@@ -45,7 +40,6 @@ impl Task for ComputeNextInnerTask<'_> {
             panic!("Not enough data in context");
         };
 
-
         let ComputeNextLayerCache {
             next_queries,
             verify_indices,
@@ -55,8 +49,9 @@ impl Task for ComputeNextInnerTask<'_> {
 
         let coset_size = &params.coset_size;
 
+        // Eager schedules have a task for every query, the ones after the last coset have nothing left.
         if queries.is_empty() {
-            return vec![];
+            return;
         }
 
         #[inline(never)]
@@ -97,16 +92,6 @@ impl Task for ComputeNextInnerTask<'_> {
             y_value: fri_formula_res,
             x_inv_value: next_x_inv,
         });
-
-        vec![Tasks::ComputeNextInner(*layer_index)]
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        if self.parent.cache.fri_queries.is_empty() {
-            return vec![];
-        } else {
-            vec![Tasks::ComputeNextInner(self.parent.layer_index)]
-        }
     }
 }
 
@@ -116,9 +101,9 @@ impl<'a> ComputeNextInnerTask<'a> {
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Self {
-        ComputeNextInnerTask {
-            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate),
-        }
+    ) -> Result<Self, ProgramError> {
+        Ok(ComputeNextInnerTask {
+            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate)?,
+        })
     }
 }
@@ -1,3 +1,4 @@
+use solana_program::program_error::ProgramError;
 use swiftness::swiftness_fri::ComputeNextLayerCache;
 use swiftness::swiftness_fri::FriVerifyCache;
 use swiftness::types::StarkProof;
@@ -6,7 +7,6 @@ use swiftness_air::swiftness_commitment::table::decommit::MONTGOMERY_R;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::layer::StarkVerifyLayerTask;
 
@@ -16,7 +16,7 @@ pub struct StarkVerifyLayerDecommitmentMontTask<'a> {
 
 impl Task for StarkVerifyLayerDecommitmentMontTask<'_> {
     // fri_verify_layers(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let FriVerifyCache {
             next_layer_cache,
             decommitment,
@@ -35,12 +35,6 @@ impl Task for StarkVerifyLayerDecommitmentMontTask<'_> {
                 .montgomery_values
                 .push(verify_y_values.get(i).unwrap() * MONTGOMERY_R);
         }
-
-        vec![]
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
@@ -50,9 +44,9 @@ impl<'a> StarkVerifyLayerDecommitmentMontTask<'a> {
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Self {
-        Self {
-            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate),
-        }
+    ) -> Result<Self, ProgramError> {
+        Ok(Self {
+            parent: StarkVerifyLayerTask::view(layer_index, proof, cache, intermediate)?,
+        })
     }
 }
@@ -1,3 +1,4 @@
+use solana_program::program_error::ProgramError;
 use swiftness::funvec::FunVec;
 use swiftness::swiftness_fri::ComputeNextLayerCache;
 use swiftness::swiftness_fri::FriVerifyCache;
@@ -9,15 +10,16 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 use crate::verify::stark_verify::table_decommit::TableDecommitCache;
 use crate::verify::stark_verify::table_decommit::TableDecommitTarget;
 use crate::verify::stark_verify::table_decommit::TableDecommitTask;
+use crate::verify::stark_verify::table_decommit::parallel::ParallelCache;
 
 pub struct StarkVerifyLayerTask<'a> {
     pub layer_index: usize,
     pub cache: &'a mut FriVerifyCache,
     pub table_cache: &'a mut TableDecommitCache,
+    pub parallel: &'a mut ParallelCache,
     pub context: Option<StarkVerifyLayerContext<'a>>,
 }
 
@@ -30,18 +32,7 @@ pub struct StarkVerifyLayerContext<'a> {
 
 impl Task for StarkVerifyLayerTask<'_> {
     // fri_verify_layers(
-    fn execute(&mut self) -> Vec<Tasks> {
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![
-            Tasks::ComputeNextLayer(self.layer_index),
-            Tasks::StarkVerifyLayerDecommitmentMont(self.layer_index),
-            Tasks::TableDecommit(TableDecommitTarget::Fri(self.layer_index as u8)),
-            Tasks::StarkVerifyLayerAssignNext,
-        ]
-    }
+    fn execute(&mut self) {}
 }
 
 impl<'a> StarkVerifyLayerTask<'a> {
@@ -50,8 +41,12 @@ impl<'a> StarkVerifyLayerTask<'a> {
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Self {
-        let Cache { legacy, table } = cache;
+    ) -> Result<Self, ProgramError> {
+        let Cache {
+            legacy,
+            table,
+            parallel,
+        } = cache;
         let cache = &mut legacy.stark.fri;
         let commitment = &intermediate.verify.stark_commitment.fri;
         let witness = &mut proof.witness.fri_witness;
@@ -65,17 +60,27 @@ impl<'a> StarkVerifyLayerTask<'a> {
         let context = if fri_step_sizes.len() != 0 {
             let step_sizes = &fri_step_sizes[1..fri_step_sizes.len()];
 
-            let target_layer_witness = layer_witness.get_mut(layer_index).unwrap();
+            let target_layer_witness = layer_witness
+                .get_mut(layer_index)
+                .ok_or(ProgramError::Custom(18))?;
             let target_layer_witness_leaves = &mut target_layer_witness.leaves;
             let target_layer_witness_table_withness = &target_layer_witness.table_witness;
-            let target_commitment = commitment_layer.get(layer_index).unwrap();
+            let target_commitment = commitment_layer
+                .get(layer_index)
+                .ok_or(ProgramError::Custom(18))?;
 
             // Params.
-            let coset_size = Felt::TWO.pow_felt(step_sizes.get(layer_index).unwrap());
+            let coset_size = Felt::TWO.pow_felt(
+                step_sizes
+                    .get(layer_index)
+                    .ok_or(ProgramError::Custom(18))?,
+            );
             let params = FriLayerComputationParams {
                 coset_size,
                 fri_group: &FRI_GROUP,
-                eval_point: *eval_points.get(layer_index).unwrap(),
+                eval_point: *eval_points
+                    .get(layer_index)
+                    .ok_or(ProgramError::Custom(18))?,
             };
 
             let context = StarkVerifyLayerContext {
@@ -90,23 +95,28 @@ impl<'a> StarkVerifyLayerTask<'a> {
             None
         };
 
-        StarkVerifyLayerTask {
+        Ok(StarkVerifyLayerTask {
             layer_index,
             cache,
             table_cache: table,
+            parallel,
             context,
-        }
+        })
     }
 }
 
-impl<'a> Into<TableDecommitTask<'a>> for StarkVerifyLayerTask<'a> {
-    fn into(self) -> TableDecommitTask<'a> {
+impl<'a> TryFrom<StarkVerifyLayerTask<'a>> for TableDecommitTask<'a> {
+    type Error = ProgramError;
+
+    /// Fails if the FRI commitment isn't known yet.
+    fn try_from(task: StarkVerifyLayerTask<'a>) -> Result<Self, Self::Error> {
         let StarkVerifyLayerTask {
+            layer_index,
             cache,
             context,
             table_cache,
-            ..
-        } = self;
+            parallel,
+        } = task;
 
         let FriVerifyCache {
             next_layer_cache,
@@ -120,17 +130,20 @@ impl<'a> Into<TableDecommitTask<'a>> for StarkVerifyLayerTask<'a> {
             ..
         }) = context
         else {
-            panic!("Not enough data in context");
+            return Err(ProgramError::Custom(18));
         };
 
         let ComputeNextLayerCache { verify_indices, .. } = next_layer_cache;
 
-        TableDecommitTask {
+        Ok(TableDecommitTask {
+            target: TableDecommitTarget::Fri(layer_index as u8),
             cache: table_cache,
+            parallel,
             commitment: &target_commitment,
             queries: verify_indices.as_slice(),
             decommitment: decommitment,
             witness: &target_layer_witness_table_withness,
-        }
+            merkle_steps: 0, // Only known from the proof, set by `TableDecommitTask::view`.
+        })
     }
 }
@@ -1,10 +1,8 @@
-use swiftness::funvec;
 use swiftness::types::StarkProof;
 
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkVerifyFriTask;
 
@@ -19,7 +17,7 @@ pub struct StarkVerifyLayersTask<'a> {
 
 impl Task for StarkVerifyLayersTask<'_> {
     // fri_verify_layers(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         // Original
 
         // let StarkVerifyFriTask {
@@ -50,15 +48,6 @@ impl Task for StarkVerifyLayersTask<'_> {
         //     eval_points,
         //     step_sizes,
         // );
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        let n_layers = funvec::cast_felt(&self.parent.config.n_layers) as usize;
-        (0..(n_layers - 1))
-            .map(|i| Tasks::StarkVerifyFriLayer(i))
-            .collect()
     }
 }
 
@@ -7,7 +7,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkVerifyFriTask;
 
@@ -17,7 +16,7 @@ pub struct StarkVerifyLastLayerTask<'a> {
 
 impl Task for StarkVerifyLastLayerTask<'_> {
     // fri_verify(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         // Original
 
         let StarkVerifyFriTask {
@@ -38,12 +37,6 @@ impl Task for StarkVerifyLastLayerTask<'_> {
         )
         .map_err(|_| Error::LastLayerVerificationError)
         .unwrap();
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
@@ -9,7 +9,6 @@ use swiftness::types::StarkProof;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::StarkVerifyIntermediate;
 
@@ -26,7 +25,7 @@ pub struct StarkVerifyFriTask<'a> {
 
 impl Task for StarkVerifyFriTask<'_> {
     // fri_verify(
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         // Original
 
         let StarkVerifyFriTask {
@@ -53,15 +52,6 @@ impl Task for StarkVerifyFriTask<'_> {
             decommitment.values,
             decommitment.points,
         );
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![
-            Tasks::StarkVerifyLayersTask,
-            Tasks::StarkVerifyLastLayerTask,
-        ]
     }
 }
 
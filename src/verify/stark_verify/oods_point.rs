@@ -0,0 +1,87 @@
+use swiftness::types::Felt;
+use swiftness::types::StarkProof;
+use swiftness_air::layout::LayoutTrait;
+use swiftness_air::layout::recursive_with_poseidon::Layout;
+
+use crate::Cache;
+use crate::intermediate::Intermediate;
+use crate::task::Task;
+
+use super::StarkVerifyTask;
+
+/// Most columns evaluated at a query point, the trace columns of the layout and the composition.
+pub const MAX_OODS_COLUMNS: usize = 32;
+
+pub struct StarkVerifyOodsPointTask<'a> {
+    query_index: usize,
+    parent: StarkVerifyTask<'a>,
+}
+
+impl Task for StarkVerifyOodsPointTask<'_> {
+    // eval_oods_boundary_poly_at_points() - single point
+    fn execute(&mut self) {
+        let StarkVerifyTask {
+            n_original_columns,
+            n_interaction_columns,
+            commitment,
+            witness,
+            stark_domains,
+            intermediate,
+            ..
+        } = &mut self.parent;
+
+        let i = self.query_index;
+        // Scheduled for `n_queries` points, there are fewer if sampled queries repeat.
+        if i >= intermediate.points.len() {
+            return;
+        }
+        let n_original_columns = *n_original_columns as usize;
+        let n_interaction_columns = *n_interaction_columns as usize;
+
+        let decommitment = &witness.traces_decommitment;
+        let composition_decommitment = &witness.composition_decommitment;
+
+        // Original, interaction and composition columns of the query, without allocating.
+        let n_columns = n_original_columns + n_interaction_columns + Layout::CONSTRAINT_DEGREE;
+        assert!(n_columns <= MAX_OODS_COLUMNS, "too many columns");
+        let mut column_values = [Felt::ZERO; MAX_OODS_COLUMNS];
+        let rows = [
+            &decommitment.original.values.as_slice()
+                [i * n_original_columns..(i + 1) * n_original_columns],
+            &decommitment.interaction.values.as_slice()
+                [i * n_interaction_columns..(i + 1) * n_interaction_columns],
+            &composition_decommitment.values.as_slice()
+                [i * Layout::CONSTRAINT_DEGREE..(i + 1) * Layout::CONSTRAINT_DEGREE],
+        ];
+        let mut len = 0;
+        for row in rows {
+            column_values[len..len + row.len()].copy_from_slice(row);
+            len += row.len();
+        }
+        let column_values = &column_values[..len];
+
+        let point = intermediate.points.as_slice()[i];
+        intermediate.evaluations.as_slice_mut()[i] = Layout::eval_oods_polynomial(
+            column_values,
+            commitment.oods_values.as_slice(),
+            commitment.interaction_after_oods.as_slice(),
+            &point,
+            &commitment.interaction_after_composition,
+            &stark_domains.trace_generator,
+        );
+    }
+}
+
+impl<'a> StarkVerifyOodsPointTask<'a> {
+    pub fn view(
+        query_index: usize,
+        proof: &'a mut StarkProof,
+        cache: &'a mut Cache,
+        intermediate: &'a mut Intermediate,
+    ) -> Self {
+        StarkVerifyOodsPointTask {
+            query_index,
+            parent: StarkVerifyTask::view(proof, cache, intermediate),
+        }
+    }
+}
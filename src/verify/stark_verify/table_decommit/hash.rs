@@ -0,0 +1,74 @@
+use starknet_crypto::{poseidon_hash, poseidon_hash_many};
+use swiftness::types::Felt;
+
+/// Hash of two Merkle tree nodes, `poseidon` in the verifier friendly layers and masked keccak otherwise.
+pub fn hash_node(x: &Felt, y: &Felt, is_verifier_friendly: bool) -> Felt {
+    if is_verifier_friendly {
+        poseidon_hash(*x, *y)
+    } else {
+        keccak_160_lsb(&[x.to_bytes_be(), y.to_bytes_be()])
+    }
+}
+
+/// Longest table row hashed with keccak: the columns of a trace table, or a coset of an FRI layer.
+pub const MAX_ROW_LENGTH: usize = 32;
+
+/// Hash of a table row, used as the leaf of the Merkle tree.
+pub fn hash_row(values: &[Felt], is_verifier_friendly: bool) -> Felt {
+    match values {
+        [value] => *value,
+        _ if is_verifier_friendly => poseidon_hash_many(values),
+        _ => {
+            assert!(values.len() <= MAX_ROW_LENGTH, "table row too long");
+            let mut bytes = [[0u8; 32]; MAX_ROW_LENGTH];
+            for (bytes, value) in bytes.iter_mut().zip(values) {
+                *bytes = value.to_bytes_be();
+            }
+            keccak_160_lsb(&bytes[..values.len()])
+        }
+    }
+}
+
+/// Keccak of the big-endian encoded felts, truncated to the 160 least significant bits.
+fn keccak_160_lsb(values: &[[u8; 32]]) -> Felt {
+    let hash = keccak256(values.as_flattened());
+    Felt::from_bytes_be_slice(&hash[12..32])
+}
+
+/// Keccak256 of the bytes, computed by the `sol_keccak256` syscall on SBF, which is far cheaper than
+/// hashing in the VM.
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    solana_program::keccak::hash(bytes).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_parity() {
+        use sha3::{Digest, Keccak256};
+
+        // `solana_program::keccak` is the syscall on SBF, and hashes in software off-chain.
+        let bytes: Vec<u8> = (0..300u32).map(|i| (i * 37 % 256) as u8).collect();
+        for len in [0, 1, 31, 32, 64, 135, 136, 137, 300] {
+            let expected: [u8; 32] = Keccak256::digest(&bytes[..len]).into();
+            assert_eq!(keccak256(&bytes[..len]), expected);
+        }
+
+        // Same as hashing the felts one by one.
+        let keccak_160_lsb = |values: &[Felt]| {
+            let mut hasher = Keccak256::new();
+            for value in values {
+                hasher.update(value.to_bytes_be());
+            }
+            Felt::from_bytes_be_slice(&hasher.finalize()[12..32])
+        };
+        let felts = [Felt::ZERO, Felt::ONE, Felt::MAX, Felt::from(0x1234_5678u64)];
+        assert_eq!(
+            hash_node(&felts[2], &felts[3], false),
+            keccak_160_lsb(&felts[2..])
+        );
+        assert_eq!(hash_row(&felts, false), keccak_160_lsb(&felts));
+    }
+}
@@ -0,0 +1,206 @@
+use solana_program::program_error::ProgramError;
+use swiftness::funvec::FUNVEC_QUERIES;
+use swiftness::types::Felt;
+use swiftness::types::StarkProof;
+use swiftness_air::Commitment;
+use swiftness_air::Witness;
+
+use crate::Cache;
+use crate::intermediate::Intermediate;
+use crate::task::Task;
+
+use super::TableDecommitTarget;
+use super::TableDecommitTask;
+use super::hash::hash_node;
+
+/// Number of Merkle nodes hashed by a single `TableDecommitMerkle` task.
+pub const MERKLE_NODES_PER_TASK: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[repr(C)]
+pub struct MerkleNode {
+    pub index: u64,
+    pub depth: u64,
+    pub value: Felt,
+}
+
+unsafe impl bytemuck::Zeroable for MerkleNode {}
+unsafe impl bytemuck::Pod for MerkleNode {}
+
+/// Queue of the Merkle nodes awaiting hashing, ordered by index within the current depth.
+///
+/// Every step consumes at least one node and produces one, so it never holds more nodes than queries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct MerkleQueue {
+    nodes: [MerkleNode; FUNVEC_QUERIES],
+    head: u32,
+    len: u32,
+    pub authentication_index: u64,
+    pub n_verifier_friendly_layers: u64,
+}
+
+unsafe impl bytemuck::Zeroable for MerkleQueue {}
+unsafe impl bytemuck::Pod for MerkleQueue {}
+
+impl Default for MerkleQueue {
+    fn default() -> Self {
+        bytemuck::Zeroable::zeroed()
+    }
+}
+
+impl MerkleQueue {
+    pub fn reset(&mut self, n_verifier_friendly_layers: u64) {
+        self.head = 0;
+        self.len = 0;
+        self.authentication_index = 0;
+        self.n_verifier_friendly_layers = n_verifier_friendly_layers;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<&MerkleNode> {
+        (i < self.len()).then(|| &self.nodes[(self.head as usize + i) % FUNVEC_QUERIES])
+    }
+
+    pub fn push(&mut self, node: MerkleNode) {
+        assert!(self.len() < FUNVEC_QUERIES, "Merkle queue overflow");
+        self.nodes[(self.head as usize + self.len()) % FUNVEC_QUERIES] = node;
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<MerkleNode> {
+        let node = *self.get(0)?;
+        self.head = ((self.head as usize + 1) % FUNVEC_QUERIES) as u32;
+        self.len -= 1;
+        Some(node)
+    }
+
+    /// Whether only the root is left, so the tree is verified.
+    pub fn reached_root(&self) -> bool {
+        self.get(0).is_some_and(|node| node.index == 1)
+    }
+}
+
+pub struct TableDecommitMerkleTask<'a> {
+    parent: TableDecommitTask<'a>,
+}
+
+impl Task for TableDecommitMerkleTask<'_> {
+    // vector_commitment_decommit() - compute_root_from_queries(), resumed
+    fn execute(&mut self) {
+        let TableDecommitTask {
+            cache,
+            commitment,
+            witness,
+            ..
+        } = &mut self.parent;
+
+        // Once the root is reached, the remaining steps only check it again.
+        cache.merkle_steps = cache
+            .merkle_steps
+            .checked_sub(1)
+            .expect("no Merkle steps left");
+        let verified = verify_merkle_steps(&mut cache.merkle, commitment, witness);
+        assert!(
+            verified || cache.merkle_steps > 0,
+            "Merkle root not reached"
+        );
+    }
+}
+
+/// Upper bound of `TableDecommitMerkle` tasks needed to reach the root of a tree of `height`
+/// from `n_queries` leaves.
+///
+/// Each step hashes a node into its parent, and at every depth there are at most
+/// `min(n_queries, 2^depth)` parents, followed by a final step checking the root.
+pub fn merkle_steps(height: u64, n_queries: u64) -> u64 {
+    let hashes: u64 = (0..height)
+        .map(|depth| n_queries.min(1u64.checked_shl(depth as u32).unwrap_or(u64::MAX)))
+        .sum();
+    (hashes + 1).div_ceil(MERKLE_NODES_PER_TASK as u64)
+}
+
+/// Hashes up to `MERKLE_NODES_PER_TASK` nodes of the queue, returns `true` once the root is verified.
+pub fn verify_merkle_steps(
+    queue: &mut MerkleQueue,
+    commitment: &Commitment,
+    witness: &Witness,
+) -> bool {
+    let authentications = witness.vector.authentications.as_slice();
+
+    for _ in 0..MERKLE_NODES_PER_TASK {
+        let current = *queue.get(0).expect("Merkle queue is empty");
+
+        // Reached the root.
+        if current.index == 1 {
+            assert_eq!(current.depth, 0, "invalid root depth");
+            assert_eq!(queue.len(), 1, "Merkle queue not exhausted");
+            assert_eq!(
+                queue.authentication_index as usize,
+                authentications.len(),
+                "unused authentication nodes"
+            );
+            assert_eq!(
+                current.value, commitment.vector_commitment.commitment_hash,
+                "Merkle root mismatch"
+            );
+            return true;
+        }
+
+        queue.pop();
+        let is_verifier_friendly = queue.n_verifier_friendly_layers >= current.depth;
+
+        let value = if current.index & 1 == 0 {
+            match queue.get(0) {
+                // Sibling is also queried.
+                Some(next) if next.index == current.index + 1 => {
+                    let next = queue.pop().unwrap();
+                    hash_node(&current.value, &next.value, is_verifier_friendly)
+                }
+                _ => {
+                    let sibling = next_authentication(queue, authentications);
+                    hash_node(&current.value, sibling, is_verifier_friendly)
+                }
+            }
+        } else {
+            let sibling = next_authentication(queue, authentications);
+            hash_node(sibling, &current.value, is_verifier_friendly)
+        };
+
+        queue.push(MerkleNode {
+            index: current.index / 2,
+            depth: current.depth - 1,
+            value,
+        });
+    }
+
+    false
+}
+
+fn next_authentication<'a>(queue: &mut MerkleQueue, authentications: &'a [Felt]) -> &'a Felt {
+    let authentication = authentications
+        .get(queue.authentication_index as usize)
+        .expect("missing authentication node");
+    queue.authentication_index += 1;
+    authentication
+}
+
+impl<'a> TableDecommitMerkleTask<'a> {
+    pub fn view(
+        target: TableDecommitTarget,
+        proof: &'a mut StarkProof,
+        cache: &'a mut Cache,
+        intermediate: &'a mut Intermediate,
+    ) -> Result<Self, ProgramError> {
+        Ok(TableDecommitMerkleTask {
+            parent: TableDecommitTask::view(target, proof, cache, intermediate)?,
+        })
+    }
+}
@@ -1,51 +1,100 @@
 use bytemuck::Pod;
 use bytemuck::Zeroable;
 use solana_program::program_error::ProgramError;
-use swiftness::stark::CacheCommitment;
+use swiftness::funvec;
 use swiftness::types::Felt;
 use swiftness::types::StarkProof;
 use swiftness_air::Commitment;
 use swiftness_air::Decommitment;
 use swiftness_air::Witness;
-use swiftness_air::swiftness_commitment::table::decommit::table_decommit;
 
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 use super::fri_verify::fri_verify_layers::layer::StarkVerifyLayerTask;
+use hash::hash_row;
+use merkle::{MerkleNode, MerkleQueue, merkle_steps};
+use parallel::ParallelCache;
+
+pub mod hash;
+pub mod merkle;
+pub mod parallel;
 
 pub struct TableDecommitTask<'a> {
+    pub target: TableDecommitTarget,
     pub cache: &'a mut TableDecommitCache,
+    pub parallel: &'a mut ParallelCache,
     pub commitment: &'a Commitment,
     pub queries: &'a [Felt],
     pub decommitment: &'a Decommitment,
     pub witness: &'a Witness,
+    pub merkle_steps: u64,
 }
 
+/// Scratch space of the table decommitment, shared by all the tables as they are verified one by one.
 #[derive(Debug, Clone, Copy, Default, Zeroable, Pod, PartialEq)]
 #[repr(C)]
 pub struct TableDecommitCache {
-    pub commitment: CacheCommitment, // TODO: minimize this;
+    pub merkle: MerkleQueue,
+    pub merkle_steps: u64, // `TableDecommitMerkle` tasks left, the last one has to reach the root.
 }
 
 impl Task for TableDecommitTask<'_> {
-    fn execute(&mut self) -> Vec<Tasks> {
-        table_decommit(
-            &mut self.cache.commitment,
-            self.commitment,
-            self.queries,
-            self.decommitment,
-            self.witness,
-        )
-        .unwrap();
+    // table_decommit() - leaves, the Merkle tree is verified by `TableDecommitMerkle` tasks
+    fn execute(&mut self) {
+        let TableDecommitTask {
+            target,
+            cache,
+            parallel,
+            commitment,
+            queries,
+            decommitment,
+            merkle_steps,
+            ..
+        } = self;
+
+        cache.merkle_steps = *merkle_steps;
+        push_leaves(&mut cache.merkle, commitment, queries, decommitment);
 
-        self.children()
+        // The Merkle tree will be verified in a work account instead, which hashes the leaves again.
+        if parallel.is_enabled() && target.slot().is_some() {
+            parallel.export(*target);
+        }
     }
+}
 
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
+/// Resets the queue to the leaves of the table, the hashes of the decommitted rows at the queries.
+pub fn push_leaves(
+    queue: &mut MerkleQueue,
+    commitment: &Commitment,
+    queries: &[Felt],
+    decommitment: &Decommitment,
+) {
+    let n_columns = funvec::cast_felt(&commitment.config.n_columns) as usize;
+    assert_eq!(
+        decommitment.montgomery_values.len(),
+        queries.len() * n_columns,
+        "invalid decommitment length"
+    );
+
+    let vector_config = &commitment.vector_commitment.config;
+    let height = funvec::cast_felt(&vector_config.height);
+    let n_verifier_friendly_layers =
+        funvec::cast_felt(&vector_config.n_verifier_friendly_commitment_layers);
+
+    // Determine if the table commitment should use a verifier friendly hash function for the bottom layer.
+    let is_bottom_layer_verifier_friendly = n_verifier_friendly_layers >= height;
+
+    queue.reset(n_verifier_friendly_layers);
+
+    let rows = decommitment.montgomery_values.as_slice().chunks(n_columns);
+    for (query, row) in queries.iter().zip(rows) {
+        queue.push(MerkleNode {
+            index: funvec::cast_felt(query) + (1 << height),
+            depth: height,
+            value: hash_row(row, is_bottom_layer_verifier_friendly),
+        });
     }
 }
 
@@ -60,64 +109,119 @@ pub enum TableDecommitTarget {
     Fri(u8) = 4,
 }
 
+impl From<TableDecommitTarget> for [u8; 2] {
+    fn from(target: TableDecommitTarget) -> Self {
+        match target {
+            TableDecommitTarget::Invalid => [0, 0],
+            TableDecommitTarget::Original => [1, 0],
+            TableDecommitTarget::Interaction => [2, 0],
+            TableDecommitTarget::Composition => [3, 0],
+            TableDecommitTarget::Fri(i) => [4, i],
+        }
+    }
+}
+
 impl TryFrom<[u8; 2]> for TableDecommitTarget {
     type Error = ProgramError;
 
+    /// `Invalid` is only a placeholder, so it's never decoded.
     fn try_from(value: [u8; 2]) -> Result<Self, Self::Error> {
         let [variant, fri] = value;
-        match variant {
-            0 => Ok(TableDecommitTarget::Invalid),
-            1 => Ok(TableDecommitTarget::Original),
-            2 => Ok(TableDecommitTarget::Interaction),
-            3 => Ok(TableDecommitTarget::Composition),
-            4 => Ok(TableDecommitTarget::Fri(fri)),
+        match (variant, fri) {
+            (1, 0) => Ok(TableDecommitTarget::Original),
+            (2, 0) => Ok(TableDecommitTarget::Interaction),
+            (3, 0) => Ok(TableDecommitTarget::Composition),
+            (4, fri) => Ok(TableDecommitTarget::Fri(fri)),
             _ => Err(ProgramError::Custom(17)),
         }
     }
 }
 
+impl TableDecommitTarget {
+    /// Number of `TableDecommitMerkle` tasks verifying the table, known from the proof parameters.
+    pub fn merkle_steps(self, proof: &StarkProof) -> u64 {
+        let config = &proof.config;
+        let vector = match self {
+            TableDecommitTarget::Original => &config.traces.original.vector,
+            TableDecommitTarget::Interaction => &config.traces.interaction.vector,
+            TableDecommitTarget::Composition => &config.composition.vector,
+            TableDecommitTarget::Fri(i) => match config.fri.inner_layers.as_slice().get(i as usize)
+            {
+                Some(layer) => &layer.vector,
+                None => return 0,
+            },
+            TableDecommitTarget::Invalid => return 0,
+        };
+
+        merkle_steps(
+            funvec::cast_felt(&vector.height),
+            funvec::cast_felt(&config.n_queries),
+        )
+    }
+}
+
 impl<'a> TableDecommitTask<'a> {
     pub fn view(
         variant: TableDecommitTarget,
         proof: &'a mut StarkProof,
         cache: &'a mut Cache,
         intermediate: &'a mut Intermediate,
-    ) -> Self {
+    ) -> Result<Self, ProgramError> {
+        let merkle_steps = variant.merkle_steps(proof);
         if let TableDecommitTarget::Fri(i) = variant {
-            return StarkVerifyLayerTask::view(i as usize, proof, cache, intermediate).into();
+            let task: Self =
+                StarkVerifyLayerTask::view(i as usize, proof, cache, intermediate)?.try_into()?;
+            return Ok(Self {
+                merkle_steps,
+                ..task
+            });
         }
 
         let queries = intermediate.verify.queries.as_slice();
-        let cache = &mut cache.table;
+        let Cache {
+            table: cache,
+            parallel,
+            ..
+        } = cache;
 
         let commitment = &intermediate.verify.stark_commitment;
         let decommitment = &proof.witness.traces_decommitment;
         let witness = &proof.witness.traces_witness;
 
-        match variant {
+        Ok(match variant {
             TableDecommitTarget::Original => TableDecommitTask {
+                target: variant,
                 cache,
+                parallel,
                 commitment: &commitment.traces.original,
                 queries,
                 decommitment: &decommitment.original,
                 witness: &witness.original,
+                merkle_steps,
             },
             TableDecommitTarget::Interaction => TableDecommitTask {
+                target: variant,
                 cache,
+                parallel,
                 commitment: &commitment.traces.interaction,
                 queries,
                 decommitment: &decommitment.interaction,
                 witness: &witness.interaction,
+                merkle_steps,
             },
             TableDecommitTarget::Composition => TableDecommitTask {
+                target: variant,
                 cache,
+                parallel,
                 commitment: &commitment.composition,
                 queries,
                 decommitment: &proof.witness.composition_decommitment,
                 witness: &proof.witness.composition_witness,
+                merkle_steps,
             },
-            TableDecommitTarget::Fri(_) => unreachable!("Fri is handled above"),
-            TableDecommitTarget::Invalid => unreachable!("TableDecommitTarget::Invalid"),
-        }
+            TableDecommitTarget::Fri(_) | TableDecommitTarget::Invalid => {
+                return Err(ProgramError::Custom(17));
+            }
+        })
     }
 }
@@ -0,0 +1,139 @@
+use bytemuck::Pod;
+use bytemuck::Zeroable;
+use swiftness::types::Felt;
+use swiftness::types::StarkProof;
+use swiftness_air::Commitment;
+use swiftness_air::Decommitment;
+use swiftness_air::Witness;
+
+use crate::Cache;
+use crate::intermediate::Intermediate;
+use crate::task::Task;
+
+use super::TableDecommitTarget;
+
+/// Number of tables that can be verified in parallel: the 2 trace tables and the composition.
+///
+/// Inner FRI layers are decommitted from the FRI cache, which the next layer overwrites, so they're
+/// always verified in the proof account.
+pub const PARALLEL_SLOTS: usize = 3;
+
+/// Tables exported for verification in separate work accounts.
+///
+/// Work accounts rebuild the leaves from the proof account, so only the progress is kept here.
+#[derive(Debug, Clone, Copy, Default, Zeroable, Pod, PartialEq)]
+#[repr(C)]
+pub struct ParallelCache {
+    pub enabled: u32,
+    pub exported: u32, // Bitmask of slots exported by `TableDecommit` tasks.
+    pub verified: u32, // Bitmask of slots merged back from verified work accounts.
+    _padding: u32,
+}
+
+impl ParallelCache {
+    pub fn reset(&mut self, enabled: bool) {
+        self.enabled = enabled as u32;
+        self.exported = 0;
+        self.verified = 0;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    pub fn export(&mut self, target: TableDecommitTarget) {
+        let slot = target.slot().expect("table can't be verified in parallel");
+        self.exported |= 1 << slot;
+    }
+
+    pub fn is_exported(&self, target: TableDecommitTarget) -> bool {
+        target
+            .slot()
+            .is_some_and(|slot| self.exported & (1 << slot) != 0)
+    }
+}
+
+impl TableDecommitTarget {
+    /// Index of the table in `ParallelCache`, `None` for tables verified in the proof account.
+    pub fn slot(self) -> Option<usize> {
+        match self {
+            TableDecommitTarget::Original => Some(0),
+            TableDecommitTarget::Interaction => Some(1),
+            TableDecommitTarget::Composition => Some(2),
+            TableDecommitTarget::Fri(_) | TableDecommitTarget::Invalid => None,
+        }
+    }
+
+    /// Table which can be verified in parallel, only read from the proof and the intermediate state.
+    ///
+    /// Valid once the queries are generated.
+    pub fn table<'a>(
+        self,
+        proof: &'a StarkProof,
+        intermediate: &'a Intermediate,
+    ) -> Option<Table<'a>> {
+        let commitment = &intermediate.verify.stark_commitment;
+        let decommitment = &proof.witness.traces_decommitment;
+        let witness = &proof.witness;
+
+        let (commitment, decommitment, witness) = match self {
+            TableDecommitTarget::Original => (
+                &commitment.traces.original,
+                &decommitment.original,
+                &witness.traces_witness.original,
+            ),
+            TableDecommitTarget::Interaction => (
+                &commitment.traces.interaction,
+                &decommitment.interaction,
+                &witness.traces_witness.interaction,
+            ),
+            TableDecommitTarget::Composition => (
+                &commitment.composition,
+                &witness.composition_decommitment,
+                &witness.composition_witness,
+            ),
+            TableDecommitTarget::Fri(_) | TableDecommitTarget::Invalid => return None,
+        };
+
+        Some(Table {
+            commitment,
+            queries: intermediate.verify.queries.as_slice(),
+            decommitment,
+            witness,
+        })
+    }
+}
+
+/// Everything a work account needs to rebuild the leaves of a table and verify its Merkle tree.
+pub struct Table<'a> {
+    pub commitment: &'a Commitment,
+    pub queries: &'a [Felt],
+    pub decommitment: &'a Decommitment,
+    pub witness: &'a Witness,
+}
+
+pub struct TableDecommitJoinTask<'a> {
+    parallel: &'a ParallelCache,
+}
+
+impl Task for TableDecommitJoinTask<'_> {
+    // Waits for all the exported tables to be verified in work accounts.
+    fn execute(&mut self) {
+        assert_eq!(
+            self.parallel.verified, self.parallel.exported,
+            "not all tables verified in parallel"
+        );
+    }
+}
+
+impl<'a> TableDecommitJoinTask<'a> {
+    pub fn view(
+        _proof: &'a mut StarkProof,
+        cache: &'a mut Cache,
+        _intermediate: &'a mut Intermediate,
+    ) -> Self {
+        TableDecommitJoinTask {
+            parallel: &cache.parallel,
+        }
+    }
+}
@@ -8,7 +8,6 @@ use swiftness_air::public_memory::PublicInput;
 use crate::Cache;
 use crate::intermediate::Intermediate;
 use crate::task::Task;
-use crate::task::Tasks;
 
 pub struct VerifyOutputTask<'a> {
     pub public_input: &'a PublicInput,
@@ -17,17 +16,11 @@ pub struct VerifyOutputTask<'a> {
 }
 
 impl Task for VerifyOutputTask<'_> {
-    fn execute(&mut self) -> Vec<Tasks> {
+    fn execute(&mut self) {
         let (program_hash, output) = Layout::verify_public_input(self.public_input).unwrap();
 
         *self.program_hash = program_hash;
         self.output.move_to(output);
-
-        self.children()
-    }
-
-    fn children(&self) -> Vec<Tasks> {
-        vec![]
     }
 }
 
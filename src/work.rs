@@ -0,0 +1,105 @@
+use bytemuck::{Pod, Zeroable};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::task::{RawTask, Tasks};
+use crate::verify::stark_verify::table_decommit::merkle::{MerkleQueue, verify_merkle_steps};
+use crate::verify::stark_verify::table_decommit::parallel::Table;
+use crate::verify::stark_verify::table_decommit::{TableDecommitTarget, push_leaves};
+use crate::{ProofAccount, VerificationStage};
+
+/// Account verifying a single table decommitment, so that tables of a proof can be verified in parallel.
+///
+/// Same as the proof account, it's prefixed with the `stage` and `AccountKind` bytes and 6 bytes of padding.
+#[derive(Clone, Copy, Default, Zeroable, Pod, Debug, PartialEq)]
+#[repr(C)]
+pub struct WorkAccount {
+    pub proof_account: [u8; 32], // Proof account the table belongs to.
+    pub target: RawTask,         // `Tasks::TableDecommit` being verified.
+    _padding: [u8; 4],
+    pub merkle: MerkleQueue,
+}
+
+/// Size of a work account, including the prefix.
+pub const WORK_ACCOUNT_SIZE: usize = 8 + size_of::<WorkAccount>();
+
+/// Verifies a part of the Merkle tree of the `target` table exported by the proof account.
+///
+/// The first call hashes the leaves from the decommitment in the proof account. Only reads the proof account,
+/// so multiple work accounts of the same proof can be cranked at once.
+pub fn verify_work(
+    proof_key: &Pubkey,
+    proof_data: &[u8],
+    work_data: &mut [u8],
+    work_stage: VerificationStage,
+    target: TableDecommitTarget,
+) -> Result<VerificationStage, ProgramError> {
+    let ProofAccount {
+        proof,
+        cache,
+        intermediate,
+        ..
+    } = bytemuck::from_bytes::<ProofAccount>(proof_data);
+    let work = bytemuck::from_bytes_mut::<WorkAccount>(work_data);
+    let raw_target = RawTask::from(Tasks::TableDecommit(target));
+
+    if !cache.parallel.is_exported(target) {
+        return Err(ProgramError::Custom(11));
+    }
+    let Table {
+        commitment,
+        queries,
+        decommitment,
+        witness,
+    } = target
+        .table(proof, intermediate)
+        .ok_or(ProgramError::Custom(11))?;
+
+    match work_stage {
+        VerificationStage::Publish => {
+            work.proof_account = proof_key.to_bytes();
+            work.target = raw_target;
+            push_leaves(&mut work.merkle, commitment, queries, decommitment);
+        }
+        VerificationStage::Verify => {
+            if work.proof_account != proof_key.to_bytes() || work.target != raw_target {
+                return Err(ProgramError::Custom(10));
+            }
+        }
+        VerificationStage::Verified => return Err(ProgramError::Custom(32)),
+    }
+
+    if verify_merkle_steps(&mut work.merkle, commitment, witness) {
+        Ok(VerificationStage::Verified)
+    } else {
+        Ok(VerificationStage::Verify)
+    }
+}
+
+/// Marks the table of a verified work account as verified in the proof account, and clears the work account.
+pub fn merge_work(
+    proof_key: &Pubkey,
+    proof_data: &mut [u8],
+    work_data: &mut [u8],
+    work_stage: VerificationStage,
+) -> Result<VerificationStage, ProgramError> {
+    let ProofAccount { cache, .. } = bytemuck::from_bytes_mut::<ProofAccount>(proof_data);
+    let work = bytemuck::from_bytes_mut::<WorkAccount>(work_data);
+
+    if work_stage != VerificationStage::Verified {
+        return Err(ProgramError::Custom(12));
+    }
+
+    if work.proof_account != proof_key.to_bytes() {
+        return Err(ProgramError::Custom(10));
+    }
+
+    let Tasks::TableDecommit(target) = Tasks::try_from(&work.target)? else {
+        return Err(ProgramError::Custom(10));
+    };
+    let slot = target.slot().ok_or(ProgramError::Custom(11))?;
+
+    cache.parallel.verified |= 1 << slot;
+    *work = WorkAccount::default();
+
+    Ok(VerificationStage::Publish)
+}
@@ -0,0 +1,179 @@
+// Each test binary only uses a part of the helpers.
+#![allow(dead_code)]
+
+use solana_program_test::{ProgramTest, ProgramTestContext, processor};
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::entrypoint::ProgramResult;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_verifier::client::{self, instructions, packing};
+use solana_verifier::{ACCOUNT_HEADER_SIZE, PROGRAM_ID};
+
+#[derive(Debug)]
+pub struct Metadata {
+    pub compute_units: u64,
+    pub logs: Vec<String>,
+}
+
+pub struct Setup {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub account: Keypair,
+}
+
+/// Starts a bank with the program, and uploads the image to a new account.
+pub async fn setup(program_test: ProgramTest, image: &[u8]) -> Setup {
+    let program_id: Pubkey = PROGRAM_ID.parse().unwrap();
+    let mut context = program_test.start_with_context().await;
+    let account = Keypair::new();
+
+    let size = client::account_size(image);
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let create = system_instruction::create_account(
+        &context.payer.pubkey(),
+        &account.pubkey(),
+        rent.minimum_balance(size),
+        size as u64,
+        &program_id,
+    );
+    send(&mut context, &[create], &[&account]).await.unwrap();
+
+    // Fragments as large as a transaction allows.
+    let fragment_size =
+        packing::max_fragment_size(&program_id, &context.payer.pubkey(), &account.pubkey());
+    for (i, data) in image.chunks(fragment_size).enumerate() {
+        let ix =
+            instructions::publish_fragment(&program_id, &account.pubkey(), i * fragment_size, data);
+        send(&mut context, &[ix], &[]).await.unwrap();
+    }
+
+    let data = account_data(&mut context, &account.pubkey()).await;
+    assert!(
+        data[ACCOUNT_HEADER_SIZE..] == image[..],
+        "uploaded data doesn't match the image"
+    );
+
+    Setup {
+        context,
+        program_id,
+        account,
+    }
+}
+
+/// Bank loading the SBF build of the program, only available with `cargo test-sbf`.
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("solana_verifier", PROGRAM_ID.parse().unwrap(), None)
+}
+
+/// Bank running the processor natively, so the full flow is tested by `cargo test` as well.
+pub fn native_program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "solana_verifier",
+        PROGRAM_ID.parse().unwrap(),
+        processor!(process_instruction_data),
+    );
+    program_test.prefer_bpf(false);
+    program_test
+}
+
+/// `process_instruction_data` over the account types of `solana-program-test`.
+///
+/// The program is built against another release of `solana-program`, so the accounts are passed
+/// through as the same keys, lamports and data.
+fn process_instruction_data(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    use solana_program::account_info::AccountInfo as ProgramAccountInfo;
+    use solana_program::pubkey::Pubkey as ProgramPubkey;
+
+    let convert = |key: &Pubkey| ProgramPubkey::new_from_array(key.to_bytes());
+    let keys: Vec<_> = accounts
+        .iter()
+        .map(|account| convert(account.key))
+        .collect();
+    let owners: Vec<_> = accounts
+        .iter()
+        .map(|account| convert(account.owner))
+        .collect();
+    let mut lamports: Vec<_> = accounts
+        .iter()
+        .map(|account| account.lamports.borrow_mut())
+        .collect();
+    let mut data: Vec<_> = accounts
+        .iter()
+        .map(|account| account.data.borrow_mut())
+        .collect();
+
+    let infos: Vec<_> = accounts
+        .iter()
+        .zip(keys.iter().zip(&owners))
+        .zip(lamports.iter_mut().zip(data.iter_mut()))
+        .map(|((account, (key, owner)), (lamports, data))| {
+            ProgramAccountInfo::new(
+                key,
+                account.is_signer,
+                account.is_writable,
+                &mut **lamports,
+                &mut **data,
+                owner,
+                account.executable,
+                account.rent_epoch,
+            )
+        })
+        .collect();
+
+    solana_verifier::process_instruction_data(&convert(program_id), &infos, instruction_data)
+        .map_err(|err| ProgramError::from(u64::from(err)))
+}
+
+/// Sends a transaction with the instructions, returns its compute units and logs.
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<Metadata, (TransactionError, Metadata)> {
+    // Identical transactions would be deduplicated, so each gets a fresh blockhash.
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+
+    let mut keypairs = vec![&context.payer];
+    keypairs.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &keypairs,
+        blockhash,
+    );
+
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    let metadata = result.metadata.unwrap();
+    let metadata = Metadata {
+        compute_units: metadata.compute_units_consumed,
+        logs: metadata.log_messages,
+    };
+
+    match result.result {
+        Ok(()) => Ok(metadata),
+        Err(err) => Err((err, metadata)),
+    }
+}
+
+pub async fn account_data(context: &mut ProgramTestContext, account: &Pubkey) -> Vec<u8> {
+    context
+        .banks_client
+        .get_account(*account)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+}
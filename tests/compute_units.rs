@@ -0,0 +1,162 @@
+//! Compute units of every task of `resources/saya.json` in the SBF VM, run with
+//! `cargo test-sbf --features cu-report`, which makes the program log the heap it used.
+//!
+//! Writes a CSV report to the target directory, and fails if any task takes more than `CU_LIMIT`
+//! compute units, which defaults to the limit of a transaction. With `CU_BASELINE` set to a report of
+//! another build, prints the change of compute units per kind of task.
+#![cfg(all(feature = "test-sbf", feature = "cu-report"))]
+
+mod common;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use common::{Setup, account_data, send};
+use solana_sdk::signer::Signer;
+use solana_verifier::VerificationStage;
+use solana_verifier::client::{self, COMPUTE_UNIT_LIMIT, instructions};
+use solana_verifier::progress::progress;
+
+/// Budget of a single transaction while measuring, so tasks above the limit can still be measured.
+const MEASURE_UNITS: u64 = 100_000_000;
+
+struct Measurement {
+    task: String,
+    parameters: String,
+    compute_units: u64,
+    heap_used: Option<usize>,
+}
+
+/// Total compute units of every kind of task in a report.
+fn totals(report: &str) -> BTreeMap<&str, u64> {
+    let mut totals = BTreeMap::new();
+    for line in report.lines().skip(1) {
+        let (task, rest) = line.split_once(',').expect("malformed report");
+        let mut fields = rest.rsplitn(3, ',');
+        let _heap_used = fields.next();
+        let compute_units: u64 = fields.next().unwrap().parse().expect("malformed report");
+
+        *totals.entry(task).or_default() += compute_units;
+    }
+    totals
+}
+
+fn heap_used(logs: &[String]) -> Option<usize> {
+    logs.iter()
+        .find_map(|log| log.strip_prefix("Program log: heap used: "))
+        .and_then(|used| used.parse().ok())
+}
+
+#[tokio::test]
+async fn test_compute_units_per_task() {
+    let limit = std::env::var("CU_LIMIT")
+        .map(|limit| limit.parse().expect("invalid CU_LIMIT"))
+        .unwrap_or(COMPUTE_UNIT_LIMIT as u64);
+
+    let image = client::prepare(include_str!("../resources/saya.json")).unwrap();
+    let mut program_test = common::program_test();
+    program_test.set_compute_max_units(MEASURE_UNITS);
+    let Setup {
+        mut context,
+        program_id,
+        account,
+    } = common::setup(program_test, &image).await;
+
+    let schedule = [
+        instructions::request_heap_frame(),
+        instructions::schedule(&program_id, &account.pubkey(), false),
+    ];
+    send(&mut context, &schedule, &[]).await.unwrap();
+
+    let mut measurements = vec![];
+    loop {
+        let data = account_data(&mut context, &account.pubkey()).await;
+        let p = progress(&data).unwrap();
+        if p.stage == VerificationStage::Verified {
+            break;
+        }
+
+        let task = format!("{:?}", p.current.unwrap());
+        let (task, parameters) = match task.split_once('(') {
+            Some((name, parameters)) => (
+                name.to_string(),
+                parameters[..parameters.len() - 1].to_string(),
+            ),
+            None => (task, String::new()),
+        };
+
+        let verify = [
+            instructions::request_heap_frame(),
+            instructions::verify_proof(&program_id, &account.pubkey()),
+        ];
+        let metadata = match send(&mut context, &verify, &[]).await {
+            Ok(metadata) => metadata,
+            Err((err, metadata)) => panic!(
+                "task {task}({parameters}) failed: {err}\n{}",
+                metadata.logs.join("\n")
+            ),
+        };
+
+        measurements.push(Measurement {
+            task,
+            parameters,
+            compute_units: metadata.compute_units,
+            heap_used: heap_used(&metadata.logs),
+        });
+    }
+
+    let mut report = String::from("task,parameters,compute_units,heap_used\n");
+    for m in &measurements {
+        let heap_used = m.heap_used.map(|used| used.to_string()).unwrap_or_default();
+        writeln!(
+            report,
+            "{},\"{}\",{},{heap_used}",
+            m.task, m.parameters, m.compute_units
+        )
+        .unwrap();
+    }
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("compute-units.csv");
+    std::fs::write(&path, &report).unwrap();
+    println!(
+        "report of {} tasks written to {}",
+        measurements.len(),
+        path.display()
+    );
+
+    // Compare with a report of another build, per kind of task.
+    if let Ok(baseline) = std::env::var("CU_BASELINE") {
+        let baseline = std::fs::read_to_string(baseline).unwrap();
+        let (before, after) = (totals(&baseline), totals(&report));
+        println!(
+            "{:<36} {:>12} {:>12} {:>8}",
+            "task", "before", "after", "change"
+        );
+        let tasks: BTreeSet<_> = before.keys().chain(after.keys()).collect();
+        for task in tasks {
+            let before = before.get(task).copied().unwrap_or_default();
+            let after = after.get(task).copied().unwrap_or_default();
+            println!(
+                "{task:<36} {before:>12} {after:>12} {:>+7.2}%",
+                (after as f64 / before as f64 - 1.0) * 100.0
+            );
+        }
+        let (before, after) = (before.values().sum::<u64>(), after.values().sum::<u64>());
+        println!(
+            "{:<36} {before:>12} {after:>12} {:>+7.2}%",
+            "total",
+            (after as f64 / before as f64 - 1.0) * 100.0
+        );
+    }
+
+    let exceeding: Vec<_> = measurements
+        .iter()
+        .filter(|m| m.compute_units > limit)
+        .map(|m| format!("{}({}): {}", m.task, m.parameters, m.compute_units))
+        .collect();
+    assert!(
+        exceeding.is_empty(),
+        "{} tasks exceed {limit} compute units:\n{}",
+        exceeding.len(),
+        exceeding.join("\n")
+    );
+}
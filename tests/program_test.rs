@@ -0,0 +1,91 @@
+//! Full flow in an in-process bank: with the native processor in `cargo test`, and against the program
+//! compiled to SBF with `cargo test-sbf`.
+
+mod common;
+
+use core::mem::offset_of;
+
+use common::{Setup, account_data, send};
+use solana_program_test::ProgramTest;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::signer::Signer;
+use solana_verifier::client::{self, COMPUTE_UNIT_LIMIT, instructions};
+use solana_verifier::intermediate::Intermediate;
+use solana_verifier::progress::progress;
+use solana_verifier::{ACCOUNT_HEADER_SIZE, Cache, ProofAccount, VerificationStage};
+
+#[tokio::test]
+async fn test_verify_proof_native() {
+    verify_proof(common::native_program_test()).await;
+}
+
+#[cfg(feature = "test-sbf")]
+#[tokio::test]
+async fn test_verify_proof_program() {
+    verify_proof(common::program_test()).await;
+}
+
+async fn verify_proof(program_test: ProgramTest) {
+    let image = client::prepare(include_str!("../resources/saya.json")).unwrap();
+    let Setup {
+        mut context,
+        program_id,
+        account,
+    } = common::setup(program_test, &image).await;
+
+    // Schedule and crank until verified, every transaction has to fit the compute limit.
+    let schedule = [
+        instructions::request_heap_frame(),
+        instructions::schedule(&program_id, &account.pubkey(), false),
+    ];
+    send(&mut context, &schedule, &[]).await.unwrap();
+
+    let mut executed = 0;
+    let mut max_units = 0;
+    loop {
+        let data = account_data(&mut context, &account.pubkey()).await;
+        let p = progress(&data).unwrap();
+        assert_eq!(p.executed, executed);
+        if p.stage == VerificationStage::Verified {
+            break;
+        }
+
+        let ixs = [
+            ComputeBudgetInstruction::set_compute_unit_limit(COMPUTE_UNIT_LIMIT),
+            instructions::request_heap_frame(),
+            instructions::verify_proof(&program_id, &account.pubkey()),
+        ];
+        let units = match send(&mut context, &ixs, &[]).await {
+            Ok(metadata) => metadata.compute_units,
+            Err((err, metadata)) => panic!(
+                "task {:?} failed: {err}\n{}",
+                p.current,
+                metadata.logs.join("\n")
+            ),
+        };
+
+        max_units = max_units.max(units);
+        executed += 1;
+    }
+    println!("executed {executed} tasks, at most {max_units} compute units per transaction");
+
+    // Same number of tasks as when verifying locally.
+    let mut local = Box::new(ProofAccount::default());
+    bytemuck::bytes_of_mut(&mut *local).copy_from_slice(&image);
+    assert_eq!(local.flow(), executed);
+
+    // In the VM rows and Merkle nodes are hashed with the keccak syscall, in software locally. The
+    // cache keeps the Merkle queue of the last table, so both have to hash to the same nodes.
+    let data = account_data(&mut context, &account.pubkey()).await;
+    let offset = ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, cache);
+    assert!(
+        data[offset..offset + size_of::<Cache>()] == *bytemuck::bytes_of(&local.cache),
+        "cache differs from the local verification"
+    );
+    let offset = ACCOUNT_HEADER_SIZE + offset_of!(ProofAccount, intermediate);
+    assert!(
+        data[offset..offset + size_of::<Intermediate>()]
+            == *bytemuck::bytes_of(&local.intermediate),
+        "intermediate values differ from the local verification"
+    );
+}
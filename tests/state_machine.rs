@@ -0,0 +1,223 @@
+//! Random sequences of instructions against the account of a valid proof.
+
+use core::mem::offset_of;
+use std::ops::Range;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::LazyLock;
+
+use proptest::prelude::*;
+use solana_verifier::task::Tasks;
+use solana_verifier::{
+    Entrypoint, Felt, ProofAccount, StarkProof, VerificationStage, process_instruction,
+};
+use swiftness::{TransformTo, parse};
+
+const PROOF: Range<usize> =
+    offset_of!(ProofAccount, proof)..offset_of!(ProofAccount, proof) + size_of::<StarkProof>();
+
+#[derive(Debug, Clone)]
+enum Op {
+    Publish {
+        offset: u32,
+        data: Vec<u8>,
+    },
+    /// Publishes bytes of the original image, so the proof stays valid.
+    Republish {
+        offset: u32,
+        len: u32,
+    },
+    Schedule,
+    ScheduleParallel,
+    ScheduleEager,
+    VerifyProof,
+    /// `VerifyProof` until it fails or the proof is verified.
+    Crank,
+    VerifyWork {
+        target: [u8; 2],
+    },
+    MergeWork,
+}
+
+fn op(account_size: u32) -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (
+            0..account_size + 64,
+            prop::collection::vec(any::<u8>(), 0..64)
+        )
+            .prop_map(|(offset, data)| Op::Publish { offset, data }),
+        (0..account_size, 0..1024u32).prop_map(|(offset, len)| Op::Republish { offset, len }),
+        Just(Op::Schedule),
+        Just(Op::ScheduleParallel),
+        Just(Op::ScheduleEager),
+        Just(Op::VerifyProof),
+        Just(Op::Crank),
+        any::<[u8; 2]>().prop_map(|target| Op::VerifyWork { target }),
+        Just(Op::MergeWork),
+    ]
+}
+
+fn verified_result(account_data: &[u8]) -> (Felt, Vec<Felt>) {
+    let ProofAccount { intermediate, .. } = bytemuck::from_bytes::<ProofAccount>(account_data);
+    (intermediate.program_hash(), intermediate.output().to_vec())
+}
+
+struct Harness {
+    original: Vec<u8>,
+    expected: (Felt, Vec<Felt>),
+    account: Vec<u8>,
+    stage: VerificationStage,
+}
+
+/// Image of the valid proof, and the result of verifying it.
+static BASELINE: LazyLock<(Vec<u8>, (Felt, Vec<Felt>))> = LazyLock::new(|| {
+    let mut original = vec![0u8; size_of::<ProofAccount>()];
+    bytemuck::from_bytes_mut::<ProofAccount>(&mut original).proof =
+        parse(include_str!("../resources/saya.json"))
+            .unwrap()
+            .transform_to();
+
+    let mut account = original.clone();
+    let mut stage = VerificationStage::Publish;
+    stage = process_instruction(Entrypoint::Schedule, &mut account, stage).unwrap();
+    while stage != VerificationStage::Verified {
+        stage = process_instruction(Entrypoint::VerifyProof, &mut account, stage).unwrap();
+    }
+
+    (original, verified_result(&account))
+});
+
+impl Harness {
+    fn new() -> Self {
+        let (original, expected) = BASELINE.clone();
+        Harness {
+            account: original.clone(),
+            original,
+            expected,
+            stage: VerificationStage::Publish,
+        }
+    }
+
+    /// Processes the instruction, reverting the account if it fails, same as the runtime.
+    fn send(&mut self, instruction: Entrypoint) -> bool {
+        let before = self.account.clone();
+        let next_task = bytemuck::from_bytes::<ProofAccount>(&self.account)
+            .schedule
+            .peek()
+            .and_then(|task| Tasks::try_from(&task).ok());
+
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            process_instruction(instruction, &mut self.account, self.stage)
+        }));
+
+        let stage = match outcome {
+            Ok(Ok(stage)) => stage,
+            Ok(Err(_)) => {
+                self.account = before;
+                return false;
+            }
+            Err(_) => {
+                // Tasks reject invalid proofs by panicking, and the join waits for work accounts.
+                // The eager schedule reads the parameters of the proof before any task does.
+                assert!(
+                    matches!(
+                        instruction,
+                        Entrypoint::VerifyProof | Entrypoint::ScheduleEager
+                    ),
+                    "{instruction:?} panicked"
+                );
+                assert!(
+                    self.account[PROOF] != self.original[PROOF]
+                        || (matches!(next_task, Some(Tasks::TableDecommitJoin))
+                            && instruction == Entrypoint::VerifyProof),
+                    "{instruction:?} panicked with the original proof at {next_task:?}"
+                );
+                self.account = before;
+                return false;
+            }
+        };
+
+        // Only fragments write to the proof, and only where they're published.
+        let written = match instruction {
+            Entrypoint::PublishFragment { offset, data } => {
+                offset as usize..offset as usize + data.len()
+            }
+            _ => PROOF.end..self.account.len(),
+        };
+        let unchanged = |range: Range<usize>| self.account[range.clone()] == before[range];
+        assert!(
+            unchanged(0..written.start) && unchanged(written.end..self.account.len()),
+            "{instruction:?} wrote outside of {written:?}"
+        );
+
+        if stage == VerificationStage::Verified {
+            assert_eq!(
+                verified_result(&self.account),
+                self.expected,
+                "verified with an unexpected result"
+            );
+        }
+
+        self.stage = stage;
+        true
+    }
+
+    fn apply(&mut self, op: &Op) {
+        match op {
+            Op::Publish { offset, data } => {
+                self.send(Entrypoint::PublishFragment {
+                    offset: *offset,
+                    data,
+                });
+            }
+            Op::Republish { offset, len } => {
+                let start = (*offset as usize).min(self.original.len());
+                let end = (start + *len as usize).min(self.original.len());
+                let data = self.original[start..end].to_vec();
+                self.send(Entrypoint::PublishFragment {
+                    offset: start as u32,
+                    data: &data,
+                });
+            }
+            Op::Schedule => {
+                self.send(Entrypoint::Schedule);
+            }
+            Op::ScheduleParallel => {
+                self.send(Entrypoint::ScheduleParallel);
+            }
+            Op::ScheduleEager => {
+                self.send(Entrypoint::ScheduleEager);
+            }
+            Op::VerifyProof => {
+                self.send(Entrypoint::VerifyProof);
+            }
+            Op::Crank => {
+                while self.stage == VerificationStage::Verify {
+                    if !self.send(Entrypoint::VerifyProof) {
+                        break;
+                    }
+                }
+            }
+            Op::VerifyWork { target } => {
+                self.send(Entrypoint::VerifyWork { target: *target });
+            }
+            Op::MergeWork => {
+                self.send(Entrypoint::MergeWork);
+            }
+        }
+    }
+}
+
+proptest! {
+    // Cranking verifies the whole proof, so keep the number of cases low.
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn test_instruction_sequences(
+        ops in prop::collection::vec(op(size_of::<ProofAccount>() as u32), 1..12)
+    ) {
+        let mut harness = Harness::new();
+        for op in &ops {
+            harness.apply(op);
+        }
+    }
+}
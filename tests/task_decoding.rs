@@ -0,0 +1,91 @@
+//! Tasks are read back from the account data, so any 4 bytes have to either decode to a task
+//! that can be viewed over a valid proof, or be rejected.
+
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+use proptest::prelude::*;
+use solana_verifier::task::{RawTask, Tasks};
+use solana_verifier::{Entrypoint, ProofAccount, VerificationStage, process_instruction};
+use swiftness::{TransformTo, parse};
+
+/// Account of a valid proof, as published and once verified, when the FRI commitment is known.
+static ACCOUNTS: LazyLock<Mutex<[Vec<u8>; 2]>> = LazyLock::new(|| {
+    let mut published = vec![0u8; size_of::<ProofAccount>()];
+    bytemuck::from_bytes_mut::<ProofAccount>(&mut published).proof =
+        parse(include_str!("../resources/saya.json"))
+            .unwrap()
+            .transform_to();
+
+    let mut verified = published.clone();
+    let mut stage = VerificationStage::Publish;
+    stage = process_instruction(Entrypoint::Schedule, &mut verified, stage).unwrap();
+    while stage != VerificationStage::Verified {
+        stage = process_instruction(Entrypoint::VerifyProof, &mut verified, stage).unwrap();
+    }
+
+    Mutex::new([published, verified])
+});
+
+/// Decodes the task and views it over each of the accounts, panics if anything does.
+fn check(raw: RawTask) {
+    let Ok(task) = Tasks::try_from(&raw) else {
+        return;
+    };
+    assert_eq!(
+        RawTask::from(task),
+        raw,
+        "{task:?} decoded from non-canonical bytes"
+    );
+
+    for account in ACCOUNTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter_mut()
+    {
+        let ProofAccount {
+            proof,
+            cache,
+            intermediate,
+            ..
+        } = bytemuck::from_bytes_mut::<ProofAccount>(account);
+        let _ = task.view(proof, cache, intermediate);
+    }
+}
+
+#[test]
+fn test_decode_task_parameters() {
+    // Every variant with every value of the first two parameter bytes.
+    for variant in 0..=u8::MAX {
+        for first in 0..=u8::MAX {
+            for second in [0, 1, 4, u8::MAX] {
+                check([variant, first, second, 0]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_decode_invalid_targets() {
+    for variant in [4, 19] {
+        assert!(Tasks::try_from(&[variant, 0, 0, 0]).is_err());
+        assert!(Tasks::try_from(&[variant, 5, 0, 0]).is_err());
+        assert!(Tasks::try_from(&[variant, 1, 1, 0]).is_err());
+    }
+    assert!(Tasks::try_from(&[2, 0, 0, 1]).is_err());
+}
+
+#[test]
+fn test_encode_query_index() {
+    // Query indices take two bytes, so they don't wrap around past 255.
+    for i in [255, 256, 300, u16::MAX as usize] {
+        let raw = RawTask::from(Tasks::StarkVerifyOodsPoint(i));
+        assert!(matches!(Tasks::try_from(&raw), Ok(Tasks::StarkVerifyOodsPoint(j)) if j == i));
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_decode_raw_task(raw in any::<RawTask>()) {
+        check(raw);
+    }
+}